@@ -1,238 +1,213 @@
 use ed2020 as base;
 
-use std::{fmt, ops};
-
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum State {
-    Occupied,
-    Empty,
-    Floor,
-}
-
-impl State {
-    fn is_occupied(&self) -> bool {
-        *self == State::Occupied
-    }
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Seats of the ferry with the seats each one looks at, computed once
+struct Layout {
+    width: usize,
+    /// Seat of each tile, `None` for the floor
+    seat_of: Vec<Option<usize>>,
+    /// Initial occupation of each seat
+    initial: Vec<bool>,
+    /// Seats seen by each seat
+    neighbours: Vec<Vec<usize>>,
 }
 
-impl fmt::Display for State {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            State::Floor => write!(f, "."),
-            State::Occupied => write!(f, "#"),
-            State::Empty => write!(f, "L"),
+impl Layout {
+    /// Parses the map, where each seat sees the first seat in each direction
+    /// no further than `reach` tiles, or at any distance if `None`
+    fn new(input: &str, reach: Option<usize>) -> Self {
+        let width = input.lines().next().map_or(0, str::len);
+        let bytes: Vec<_> = input.lines().flat_map(str::bytes).collect();
+        let height = bytes.len() / width.max(1);
+        let mut seat_of = vec![None; bytes.len()];
+        // tile of each seat, row by row
+        let (mut tiles, mut initial) = (vec![], vec![]);
+        for (tile, &b) in bytes.iter().enumerate() {
+            match b {
+                b'.' => continue,
+                b'L' | b'#' => initial.push(b == b'#'),
+                _ => panic!("unknown tile {:?}", b as char),
+            }
+            seat_of[tile] = Some(tiles.len());
+            tiles.push(tile);
         }
-    }
-}
-
-#[derive(Clone, Copy, Debug)]
-struct Tile {
-    state: State,
-    change: bool,
-}
 
-impl Tile {
-    pub fn new(c: char) -> Self {
+        let first_seen = |tile: usize, (dx, dy)| {
+            let (mut x, mut y) = (tile % width, tile / width);
+            for _ in 0..reach.unwrap_or(usize::MAX) {
+                x = x.checked_add_signed(dx).filter(|&x| x < width)?;
+                y = y.checked_add_signed(dy).filter(|&y| y < height)?;
+                if let Some(seat) = seat_of[x + y * width] {
+                    return Some(seat);
+                }
+            }
+            None
+        };
+        let neighbours = tiles
+            .iter()
+            .map(|&tile| {
+                DIRECTIONS
+                    .iter()
+                    .filter_map(|&d| first_seen(tile, d))
+                    .collect()
+            })
+            .collect();
         Self {
-            state: match c {
-                '.' => State::Floor,
-                'L' => State::Empty,
-                '#' => State::Occupied,
-                _ => unreachable!(),
-            },
-            change: false,
+            width,
+            seat_of,
+            initial,
+            neighbours,
         }
     }
 
-    fn change(&mut self) -> bool {
-        if self.change {
-            self.state = match self.state {
-                State::Empty => State::Occupied,
-                State::Occupied => State::Empty,
-                State::Floor => State::Floor,
-            };
-            self.change = false;
-            true
-        } else {
-            false
+    /// Draws the seats, `#` for the occupied ones and `L` for the empty ones
+    fn render(&self, occupied: &[bool]) -> String {
+        let mut map = String::new();
+        for (tile, seat) in self.seat_of.iter().enumerate() {
+            map.push(match seat {
+                None => '.',
+                Some(s) if occupied[*s] => '#',
+                Some(_) => 'L',
+            });
+            if (tile + 1) % self.width == 0 {
+                map.push('\n');
+            }
         }
+        map
     }
 }
 
-impl ops::Deref for Tile {
-    type Target = State;
-    fn deref(&self) -> &Self::Target {
-        &self.state
-    }
+/// Seats changing all at once, with one buffer for the current round and one
+/// for the next
+struct Automaton<'a> {
+    layout: &'a Layout,
+    /// Number of occupied seats seen that make people leave
+    tolerance: usize,
+    current: Vec<bool>,
+    next: Vec<bool>,
 }
 
-impl fmt::Display for Tile {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.change {
-            write!(f, ">{}<", self.state)
-        } else {
-            write!(f, " {} ", self.state)
+impl<'a> Automaton<'a> {
+    fn new(layout: &'a Layout, tolerance: usize) -> Self {
+        Self {
+            layout,
+            tolerance,
+            current: layout.initial.clone(),
+            next: layout.initial.clone(),
         }
     }
-}
-
-#[derive(Debug)]
-struct Ferry {
-    tiles: Vec<Vec<Tile>>,
-}
 
-impl Ferry {
-    fn new(s: &str) -> Self {
-        let mut tiles = Vec::new();
-        let mut row = Vec::new();
-        for line in s.lines() {
-            for ch in line.chars() {
-                row.push(Tile::new(ch));
-            }
-            let len = row.len();
-            tiles.push(row);
-            row = Vec::with_capacity(len);
+    /// Plays a round, returning whether any seat changed
+    fn step(&mut self) -> bool {
+        let mut changed = false;
+        for (seat, neighbours) in self.layout.neighbours.iter().enumerate() {
+            let seen = neighbours.iter().filter(|&&n| self.current[n]).count();
+            let occupied = match self.current[seat] {
+                false => seen == 0,
+                true => seen < self.tolerance,
+            };
+            changed |= occupied != self.current[seat];
+            self.next[seat] = occupied;
         }
-        Self { tiles }
+        std::mem::swap(&mut self.current, &mut self.next);
+        changed
     }
 
-    fn height(&self) -> usize {
-        self.tiles.len()
+    /// Occupied seats of every round, starting from the initial ones and
+    /// stopping once they don't change anymore
+    fn frames(mut self) -> impl Iterator<Item = Vec<bool>> + 'a {
+        let mut done = false;
+        std::iter::from_fn(move || {
+            let frame = (!done).then(|| self.current.clone());
+            done = done || !self.step();
+            frame
+        })
     }
 
-    fn width(&self) -> usize {
-        self.tiles[0].len()
+    /// Number of occupied seats once nothing changes
+    fn fixpoint(mut self) -> usize {
+        while self.step() {}
+        self.current.iter().filter(|&&o| o).count()
     }
+}
 
-    fn predict_next(&mut self) -> bool {
-        let (h, w) = (self.height(), self.width());
-        for (y, x) in (0..h).flat_map(|y| (0..w).map(move |x| (y, x))) {
-            let change = match self.tiles[y][x] {
-                Tile { change: true, .. } => true,
-                Tile {
-                    state: State::Empty,
-                    ..
-                } => {
-                    let mut b = false;
-                    base::neighbours![
-                        [x] as x in 0..w,
-                        [y] as y in 0..h
-                        => b = b || self.tiles[y][x].is_occupied()
-                    ];
-                    !b
-                }
-                Tile {
-                    state: State::Occupied,
-                    ..
-                } => {
-                    let mut occ = 0;
-                    base::neighbours![
-                        [x] as x in 0..w,
-                        [y] as y in 0..h
-                        => occ += self.tiles[y][x].is_occupied() as usize
-                    ];
-                    occ > 3
-                }
-                _ => false,
-            };
-            self.tiles[y][x].change = change;
-        }
-        self.tiles
-            .iter_mut()
-            .flat_map(|v| v.iter_mut())
-            .fold(false, |p, t| t.change() || p)
-    }
+fn main() {
+    let input = base::get_input(11).unwrap();
 
-    fn sees_occupied(&self, mut x: usize, mut y: usize, dir: (isize, isize)) -> bool {
-        while ((x > 0 && x + 1 < self.width()) || dir.0 == 0)
-            && ((y > 0 && y + 1 < self.height()) || dir.1 == 0)
-            && !matches!(*self.tiles[y][x], State::Occupied | State::Empty)
-        {
-            if dir.0.is_positive() {
-                x += dir.0 as usize;
-            } else {
-                x -= dir.0.abs() as usize;
-            }
-            if dir.1.is_positive() {
-                y += dir.1 as usize;
-            } else {
-                y -= dir.1.abs() as usize;
-            }
-        }
-        self.tiles[y][x].is_occupied()
-    }
+    let adjacent = Layout::new(&input, Some(1));
+    let visible = Layout::new(&input, None);
 
-    fn predict_next_better(&mut self) -> bool {
-        let (h, w) = (self.height(), self.width());
-        for (y, x) in (0..h).flat_map(|y| (0..w).map(move |x| (y, x))) {
-            let change = match self.tiles[y][x] {
-                Tile { change: true, .. } => true,
-                Tile {
-                    state: State::Empty,
-                    ..
-                } => {
-                    let mut b = false;
-                    base::neighbours![
-                        [x, ox] as x in 0..w,
-                        [y, oy] as y in 0..h
-                        => b = b || self.sees_occupied(x, y, (ox, oy))
-                    ];
-                    !b
-                }
-                Tile {
-                    state: State::Occupied,
-                    ..
-                } => {
-                    let mut occ = 0;
-                    base::neighbours![
-                        [x, ox] as x in 0..w,
-                        [y, oy] as y in 0..h
-                        => occ += self.sees_occupied(x, y, (ox, oy)) as usize
-                    ];
-                    occ > 4
-                }
-                _ => false,
-            };
-            self.tiles[y][x].change = change;
+    if let Some("--frames") = std::env::args().nth(1).as_deref() {
+        for frame in Automaton::new(&visible, 5).frames() {
+            println!("{}", visible.render(&frame));
         }
-        self.tiles
-            .iter_mut()
-            .flat_map(|v| v.iter_mut())
-            .fold(false, |p, t| t.change() || p)
-    }
-
-    fn occupied(&self) -> usize {
-        self.tiles
-            .iter()
-            .flat_map(|v| v.iter())
-            .filter(|t| t.is_occupied())
-            .count()
+        return;
     }
+    println!("Occupied: {}", Automaton::new(&adjacent, 4).fixpoint());
+    println!("Occupied: {}", Automaton::new(&visible, 5).fixpoint());
 }
 
-impl fmt::Display for Ferry {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for row in self.tiles.iter() {
-            for tile in row.iter() {
-                write!(f, "{}", tile)?;
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::{Automaton, Layout};
+
+    const TEST_INPUT: &str = "\
+        L.LL.LL.LL\n\
+        LLLLLLL.LL\n\
+        L.L.L..L..\n\
+        LLLL.LL.LL\n\
+        L.LL.LL.LL\n\
+        L.LLLLL.LL\n\
+        ..L.L.....\n\
+        LLLLLLLLLL\n\
+        L.LLLLLL.L\n\
+        L.LLLLL.LL\n";
+
+    #[test]
+    fn test() {
+        let adjacent = Layout::new(TEST_INPUT, Some(1));
+        assert_eq!(Automaton::new(&adjacent, 4).fixpoint(), 37);
+        let visible = Layout::new(TEST_INPUT, None);
+        assert_eq!(Automaton::new(&visible, 5).fixpoint(), 26);
     }
-}
 
-fn main() {
-    let input = base::get_input(11).unwrap();
-
-    let mut ferry = Ferry::new(&input);
-    let mut furry = Ferry::new(&input);
-
-    while ferry.predict_next() {}
-    println!("Occupied: {}", ferry.occupied());
+    #[test]
+    fn frames() {
+        let layout = Layout::new(TEST_INPUT, None);
+        let frames: Vec<_> = Automaton::new(&layout, 5).frames().collect();
+        assert_eq!(frames.len(), 7);
+        assert_eq!(layout.render(&frames[0]), TEST_INPUT);
+        assert_eq!(layout.render(&frames[1]), TEST_INPUT.replace('L', "#"));
+        let third = "\
+            #.LL.LL.L#\n\
+            #LLLLLL.LL\n\
+            L.L.L..L..\n\
+            LLLL.LL.LL\n\
+            L.LL.LL.LL\n\
+            L.LLLLL.LL\n\
+            ..L.L.....\n\
+            LLLLLLLLL#\n\
+            #.LLLLLL.L\n\
+            #.LLLLL.L#\n";
+        assert_eq!(layout.render(&frames[2]), third);
+    }
 
-    while furry.predict_next_better() {
-        // println!("{}", furry)
+    #[test]
+    fn visibility() {
+        // the seats on the edges see along the edges
+        let layout = Layout::new("L.L\n...\nL.L\n", None);
+        assert_eq!(layout.neighbours[0], [1, 2, 3]);
+        let layout = Layout::new("L.L\n...\nL.L\n", Some(1));
+        assert!(layout.neighbours.iter().all(Vec::is_empty));
     }
-    println!("Occupied: {}", furry.occupied());
 }
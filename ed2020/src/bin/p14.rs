@@ -42,58 +42,78 @@ impl str::FromStr for BitMask {
     }
 }
 
-impl IntoIterator for BitMask {
-    type IntoIter = BitMaskIterator;
-    type Item = BitMask;
-
-    fn into_iter(self) -> Self::IntoIter {
-        BitMaskIterator {
-            mask: self.and,
-            v: self.or,
-            i: 0,
+/// Set of addresses where the floating bits can be either 0 or 1
+#[derive(Copy, Clone, Debug)]
+struct Pattern {
+    /// Value of the bits that are not floating
+    fixed: u64,
+    floating: u64,
+}
+
+impl Pattern {
+    fn new(mask: BitMask, addr: u64) -> Self {
+        Self {
+            fixed: (addr | mask.or) & !mask.and,
+            floating: mask.and,
         }
     }
-}
 
-struct BitMaskIterator {
-    v: u64,
-    mask: u64,
-    i: u64,
+    fn len(self) -> u64 {
+        1 << self.floating.count_ones()
+    }
+
+    fn intersects(self, other: Self) -> bool {
+        let fixed = !(self.floating | other.floating);
+        (self.fixed ^ other.fixed) & fixed == 0
+    }
+
+    /// Splits the addresses that are not in `other` into disjoint patterns
+    fn subtract(self, other: Self, out: &mut Vec<Self>) {
+        if !self.intersects(other) {
+            return out.push(self);
+        }
+        let mut rest = self;
+        let mut split = self.floating & !other.floating;
+        while split != 0 {
+            let bit = split & split.wrapping_neg();
+            split ^= bit;
+            rest.floating ^= bit;
+            out.push(Self {
+                fixed: rest.fixed | (!other.fixed & bit),
+                floating: rest.floating,
+            });
+            rest.fixed |= other.fixed & bit;
+        }
+    }
 }
 
-impl Iterator for BitMaskIterator {
-    type Item = BitMask;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.i >= 1 << self.mask.count_ones() {
-            None
-        } else {
-            let mut a = 0;
-            let mut b = 1;
-            let mut c = 1 << self.mask.trailing_zeros();
-            while b <= self.i {
-                if b & self.i > 0 {
-                    a |= c;
-                }
-                c <<= 1;
-                while c <= self.mask && c & self.mask == 0 {
-                    c <<= 1;
-                }
-                b <<= 1;
-            }
-            self.i += 1;
-            Some(BitMask {
-                and: !(self.v + self.mask),
-                or: a + self.v,
-            })
+/// Memory where each write goes to a whole pattern of addresses, stored
+/// without enumerating them
+#[derive(Default)]
+struct FloatingMemory(Vec<(Pattern, u64)>);
+
+impl FloatingMemory {
+    fn write(&mut self, at: Pattern, value: u64) {
+        let mut pieces = vec![];
+        let mut writes = vec![];
+        for (pattern, v) in self.0.drain(..) {
+            pattern.subtract(at, &mut pieces);
+            writes.extend(pieces.drain(..).map(|p| (p, v)));
+        }
+        if value != 0 {
+            writes.push((at, value));
         }
+        self.0 = writes;
+    }
+
+    fn sum(&self) -> u64 {
+        self.0.iter().map(|(p, v)| p.len() * v).sum()
     }
 }
 
-fn main() {
-    let input = base::get_input(14).unwrap();
+fn run(input: &str) -> (u64, u64) {
     let mut mem = HashMap::new();
-    let mut mem_mapped = HashMap::new();
+    let mut mem_mapped = FloatingMemory::default();
 
     let mut mask = BitMask::new();
     for line in input.lines() {
@@ -111,21 +131,119 @@ fn main() {
                 } else {
                     mem.insert(idx, x);
                 }
-                if value == 0 {
-                    for idx_mask in mask {
-                        mem_mapped.remove(&idx_mask.apply(idx));
-                    }
-                } else {
-                    for idx_mask in mask {
-                        mem_mapped.insert(idx_mask.apply(idx), value);
-                    }
-                }
+                mem_mapped.write(Pattern::new(mask, idx), value);
             }
             _ => panic!(),
         }
     }
-    let sum = mem.values().sum::<u64>();
-    let sum_mapped = mem_mapped.values().sum::<u64>();
+    (mem.values().sum(), mem_mapped.sum())
+}
+
+fn main() {
+    let input = base::get_input(14).unwrap();
+    let (sum, sum_mapped) = run(&input);
     println!("Sum: {}", sum);
     println!("Mapped: {}", sum_mapped);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl IntoIterator for BitMask {
+        type IntoIter = BitMaskIterator;
+        type Item = BitMask;
+
+        fn into_iter(self) -> Self::IntoIter {
+            BitMaskIterator {
+                mask: self.and,
+                v: self.or,
+                i: 0,
+            }
+        }
+    }
+
+    pub(super) struct BitMaskIterator {
+        v: u64,
+        mask: u64,
+        i: u64,
+    }
+
+    impl Iterator for BitMaskIterator {
+        type Item = BitMask;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.i >= 1 << self.mask.count_ones() {
+                None
+            } else {
+                let mut a = 0;
+                let mut b = 1;
+                let mut c = 1 << self.mask.trailing_zeros();
+                while b <= self.i {
+                    if b & self.i > 0 {
+                        a |= c;
+                    }
+                    c <<= 1;
+                    while c <= self.mask && c & self.mask == 0 {
+                        c <<= 1;
+                    }
+                    b <<= 1;
+                }
+                self.i += 1;
+                Some(BitMask {
+                    and: !(self.v + self.mask),
+                    or: a + self.v,
+                })
+            }
+        }
+    }
+
+    /// Sum of the memory obtained by writing every address of the patterns
+    fn enumerated(input: &str) -> u64 {
+        let mut mem = HashMap::new();
+        let mut mask = BitMask::new();
+        for line in input.lines() {
+            let (istr, val) = base::split_at(line, '=').unwrap();
+            let (istr, val) = (&istr[..istr.len() - 1], &val[1..]);
+            if istr == "mask" {
+                mask = val.parse().unwrap();
+                continue;
+            }
+            let idx: u64 = istr[4..istr.len() - 1].parse().unwrap();
+            for idx_mask in mask {
+                mem.insert(idx_mask.apply(idx), val.parse().unwrap());
+            }
+        }
+        mem.values().sum()
+    }
+
+    const TEST_INPUT: &str = "\
+        mask = 000000000000000000000000000000X1001X\n\
+        mem[42] = 100\n\
+        mask = 00000000000000000000000000000000X0XX\n\
+        mem[26] = 1\n";
+
+    #[test]
+    fn small() {
+        assert_eq!(run(TEST_INPUT).1, 208);
+        let input = format!(
+            "{TEST_INPUT}\
+            mask = 0000000000000000000000000000X0X1X0XX\n\
+            mem[7] = 0\n\
+            mem[33] = 9\n\
+            mask = 0000000000000000000000000000XXXXX0X1\n\
+            mem[12] = 3\n"
+        );
+        assert_eq!(run(&input).1, enumerated(&input));
+    }
+
+    #[test]
+    fn many_floating() {
+        let input = "\
+            mask = 00XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX\n\
+            mem[0] = 1\n\
+            mask = 0000XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX1\n\
+            mem[0] = 3\n";
+        assert_eq!(run(input).1, (1 << 34) + 2 * (1 << 31));
+    }
+}
@@ -1,54 +1,78 @@
 use ed2020 as base;
 
-use std::collections::hash_map::{Entry, HashMap};
+use std::mem;
 
-fn main() {
-    let input = base::get_input(15).unwrap();
+/// Numbers spoken in the memory game: after the starting numbers, each number
+/// is how many turns apart the previous one was spoken the last two times, or
+/// 0 if it was spoken for the first time
+struct VanEck<'a> {
+    start: &'a [u32],
+    /// Turn after the one in which each number was last spoken, 0 if never
+    last_seen: Vec<u32>,
+    turn: u32,
+    next: u32,
+}
 
-    let mut vec: Vec<(usize, usize)> = Vec::new();
-    let mut map: HashMap<usize, usize> = HashMap::new();
+impl<'a> VanEck<'a> {
+    /// Doesn't reallocate until the given turn, as no spoken number can be
+    /// greater than the number of turns
+    fn with_capacity(start: &'a [u32], turns: usize) -> Self {
+        Self {
+            start,
+            last_seen: vec![0; turns.max(start.len())],
+            turn: 0,
+            next: 0,
+        }
+    }
 
-    let mut split = input.split(',').enumerate();
-    let mut spoken = split.next().unwrap().1.parse().unwrap();
-    for (turn, line) in split {
-        let n: usize = line.parse().unwrap();
-        // spoken.map(|s| vec.push((s, turn)));
-        map.insert(spoken, turn);
-        spoken = n;
+    /// Number spoken in the given turn, starting from 1
+    fn spoken(start: &[u32], turn: usize) -> u32 {
+        VanEck::with_capacity(start, turn).nth(turn - 1).unwrap()
     }
-    vec.sort_unstable_by_key(|n| n.0);
-
-    for turn in map.len() + 1..2020 {
-        let new;
-        match map.entry(spoken) {
-            Entry::Occupied(e) => {
-                new = turn - e.get();
-                let key = *e.key();
-                *map.get_mut(&key).unwrap() = turn;
-            }
-            Entry::Vacant(e) => {
-                new = 0;
-                e.insert(turn);
-            }
+}
+
+impl Iterator for VanEck<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let turn = self.turn as usize;
+        let spoken = self.start.get(turn).copied().unwrap_or(self.next);
+        let idx = spoken as usize;
+        if idx >= self.last_seen.len() {
+            let len = (idx + 1).max(self.last_seen.len() * 2);
+            self.last_seen.resize(len, 0);
         }
-        spoken = new;
+        self.turn += 1;
+        self.next = match mem::replace(&mut self.last_seen[idx], self.turn) {
+            0 => 0,
+            last => self.turn - last,
+        };
+        Some(spoken)
     }
-    println!("2020th: {}", spoken);
-
-    for turn in 2020..30000000 {
-        let new;
-        match map.entry(spoken) {
-            Entry::Occupied(e) => {
-                new = turn - e.get();
-                let key = *e.key();
-                *map.get_mut(&key).unwrap() = turn;
-            }
-            Entry::Vacant(e) => {
-                new = 0;
-                e.insert(turn);
-            }
-        }
-        spoken = new;
+}
+
+fn main() {
+    let input = base::get_input(15).unwrap();
+    let start: Vec<u32> = input
+        .trim()
+        .split(',')
+        .map(|n| n.parse().unwrap())
+        .collect();
+
+    println!("2020th: {}", VanEck::spoken(&start, 2020));
+    println!("30000000th: {}", VanEck::spoken(&start, 30000000));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VanEck;
+
+    #[test]
+    fn test() {
+        let start = [0, 3, 6];
+        let first: Vec<_> = VanEck::with_capacity(&start, 10).take(10).collect();
+        assert_eq!(first, [0, 3, 6, 0, 3, 3, 1, 0, 4, 0]);
+        assert_eq!(VanEck::spoken(&start, 2020), 436);
+        assert_eq!(VanEck::spoken(&[3, 1, 2], 2020), 1836);
     }
-    println!("30000000th: {}", spoken);
 }
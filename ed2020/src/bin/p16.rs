@@ -2,6 +2,8 @@ use ed2020 as base;
 
 use std::{ops, str};
 
+use aoc::assignment::{Candidates, Unsolvable};
+
 struct Rule {
     name: String,
     a: ops::RangeInclusive<usize>,
@@ -34,65 +36,119 @@ impl str::FromStr for Rule {
     }
 }
 
-fn main() {
-    let input = base::get_input(16).unwrap();
-    let mut lines = input.lines();
-    let mut rules = Vec::<Rule>::new();
+struct Notes {
+    rules: Vec<Rule>,
+    my: Vec<usize>,
+    nearby: Vec<Vec<usize>>,
+}
+
+impl str::FromStr for Notes {
+    type Err = ();
 
-    for line in lines.by_ref().take_while(|s| !s.is_empty()) {
-        rules.push(line.parse().unwrap());
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        let mut rules = Vec::<Rule>::new();
+        for line in lines.by_ref().take_while(|s| !s.is_empty()) {
+            rules.push(line.parse()?);
+        }
+        let ticket = |line: &str| line.split(',').map(|s| s.parse().or(Err(()))).collect();
+
+        if lines.next() != Some("your ticket:") {
+            return Err(());
+        }
+        let my = ticket(lines.next().ok_or(())?)?;
+        lines.next();
+        if lines.next() != Some("nearby tickets:") {
+            return Err(());
+        }
+        let nearby = lines.map(ticket).collect::<Result<_, _>>()?;
+        Ok(Notes { rules, my, nearby })
     }
+}
 
-    let mut invalid_for = vec![Vec::<usize>::new(); rules.len()];
+impl Notes {
+    fn is_valid(&self, n: usize) -> bool {
+        self.rules.iter().any(|r| r.is_valid(n))
+    }
 
-    assert_eq!(lines.next(), Some("your ticket:"));
+    /// Sum of the values of the nearby tickets that aren't valid for any rule
+    fn error_rate(&self) -> usize {
+        let values = self.nearby.iter().flatten();
+        values.filter(|&&n| !self.is_valid(n)).sum()
+    }
 
-    let mut my = Vec::<usize>::with_capacity(rules.len());
-    for s in lines.next().unwrap().split(',') {
-        my.push(s.parse().unwrap());
+    /// Index of the rule of each field, found using only the nearby tickets
+    /// with all valid values
+    fn fields(&self) -> Result<Vec<usize>, Unsolvable> {
+        let valid: Vec<_> = self
+            .nearby
+            .iter()
+            .filter(|t| t.iter().all(|&n| self.is_valid(n)))
+            .collect();
+        let candidates = Candidates::from_fn(self.rules.len(), |field, rule| {
+            valid.iter().all(|t| self.rules[rule].is_valid(t[field]))
+        });
+        candidates.solve()
     }
 
-    lines.next();
-    assert_eq!(lines.next(), Some("nearby tickets:"));
-
-    let mut sum = 0;
-    for line in lines {
-        for (i, s) in line.split(',').enumerate() {
-            let n: usize = s.parse().unwrap();
-            if !rules.iter().any(|r| r.is_valid(n)) {
-                sum += n;
-            } else {
-                for (j, _) in rules.iter().enumerate().filter(|(_, v)| v.is_valid(n)) {
-                    invalid_for[i].push(j);
-                }
-            }
-        }
+    /// Product of the fields of my ticket whose name starts with `prefix`
+    fn product(&self, prefix: &str) -> Result<usize, Unsolvable> {
+        let fields = self.fields()?;
+        let mine = fields.iter().zip(&self.my);
+        let values = mine.filter(|(&r, _)| self.rules[r].name.starts_with(prefix));
+        Ok(values.map(|(_, &n)| n).product())
     }
+}
 
-    let mut names = vec![""; rules.len()];
-    let mut remaining = (0..rules.len()).sum::<usize>();
-    for len in (0..rules.len()).rev() {
-        for (j, list) in invalid_for.iter().enumerate() {
-            if list.len() == len - 1 {
-                let n = remaining - list.iter().sum::<usize>();
-                names[j] = &rules[n].name;
-                remaining -= n;
-                for list in invalid_for.iter_mut() {
-                    if let Some(i) = list.iter().position(|j| *j == n) {
-                        list.remove(i);
-                    }
-                }
-                break;
-            }
-        }
+fn main() {
+    let input = base::get_input(16).unwrap();
+    let notes: Notes = input.parse().unwrap();
+    println!("Sum: {}", notes.error_rate());
+    match notes.product("departure ") {
+        Ok(prod) => println!("Product: {}", prod),
+        Err(err) => println!("Fields: {}", err),
     }
+}
 
-    let mut prod = 1;
-    for (i, name) in names.iter().enumerate() {
-        if name.starts_with("departure ") {
-            prod *= my[i];
-        }
+#[cfg(test)]
+mod tests {
+    use super::Notes;
+
+    #[test]
+    fn test() {
+        let notes: Notes = "\
+            class: 1-3 or 5-7\n\
+            row: 6-11 or 33-44\n\
+            seat: 13-40 or 45-50\n\
+            \n\
+            your ticket:\n\
+            7,1,14\n\
+            \n\
+            nearby tickets:\n\
+            7,3,47\n\
+            40,4,50\n\
+            55,2,20\n\
+            38,6,12"
+            .parse()
+            .unwrap();
+        assert_eq!(notes.error_rate(), 71);
+
+        let notes: Notes = "\
+            class: 0-1 or 4-19\n\
+            row: 0-5 or 8-19\n\
+            seat: 0-13 or 16-19\n\
+            \n\
+            your ticket:\n\
+            11,12,13\n\
+            \n\
+            nearby tickets:\n\
+            3,9,18\n\
+            15,1,5\n\
+            5,14,9"
+            .parse()
+            .unwrap();
+        assert_eq!(notes.fields(), Ok(vec![1, 0, 2]));
+        assert_eq!(notes.product("s"), Ok(13));
+        assert_eq!(notes.product(""), Ok(11 * 12 * 13));
     }
-    println!("Sum: {}", sum);
-    println!("Product: {}", prod);
 }
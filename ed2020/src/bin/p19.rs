@@ -1,5 +1,6 @@
 use ed2020 as base;
 
+use aoc::dot::Dot;
 use std::collections::HashMap;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -108,6 +109,7 @@ impl Rule {
                     Token::Or => {
                         trace.at += 1;
                         let input = &trace.input[trace.from..];
+                        let from = trace.from;
                         let opt = stack.get(stack.len() - 2);
                         if opt.is_some() {
                             let t = opt.unwrap().clone();
@@ -121,7 +123,7 @@ impl Rule {
                             })
                         } else {
                             // This is the first rule, thus it matches
-                            result = Some(trace.from);
+                            result = Some(from);
                             stack.pop();
                         }
                     }
@@ -139,6 +141,47 @@ impl Rule {
     }
 }
 
+/// Graph of the rules with an edge for each rule referenced by another,
+/// labeled with the alternative it's part of when there is more than one
+fn dot(rules: &HashMap<usize, Rule>) -> Dot {
+    let mut dot = Dot::new();
+    let mut indices: Vec<_> = rules.keys().copied().collect();
+    indices.sort_unstable();
+    let nodes: HashMap<_, _> = indices
+        .iter()
+        .map(|&idx| {
+            let tks = rules[&idx].tks.iter().map(|tk| match tk {
+                Token::Index(i) => i.to_string(),
+                Token::Or => "|".to_string(),
+                Token::Value(c) => format!("\"{}\"", c),
+            });
+            let label = format!("{}: {}", idx, tks.collect::<Vec<_>>().join(" "));
+            (idx, dot.node(label))
+        })
+        .collect();
+    let mut recursive = vec![];
+    for idx in indices {
+        let tks = &rules[&idx].tks;
+        let alternatives = tks.split(|tk| *tk == Token::Or);
+        let labeled = tks.contains(&Token::Or);
+        for (alt, tks) in alternatives.enumerate() {
+            for tk in tks {
+                let &Token::Index(i) = tk else { continue };
+                match labeled {
+                    true => dot.labeled_edge(nodes[&idx], nodes[&i], alt + 1),
+                    false => dot.edge(nodes[&idx], nodes[&i]),
+                }
+                if i == idx {
+                    recursive.push(nodes[&idx]);
+                }
+            }
+        }
+    }
+    dot.highlight(recursive, "lightcoral");
+    dot.highlight(nodes.get(&0).copied(), "gold");
+    dot
+}
+
 fn main() {
     let input = base::get_input(19).unwrap();
     let mut rules = HashMap::new();
@@ -149,6 +192,10 @@ fn main() {
         let idx: usize = idx.parse().unwrap();
         rules.insert(idx, Rule::parse(rest));
     }
+    if std::env::args().any(|arg| arg == "--dot") {
+        print!("{}", dot(&rules));
+        return;
+    }
 
     let mut count = 0;
     for s in lines.clone() {
@@ -193,3 +240,24 @@ fn main() {
     }
     println!("Matching: {}", count);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{dot, Rule};
+    use std::collections::HashMap;
+
+    #[test]
+    fn graph() {
+        let rules = HashMap::from([
+            (0, Rule::parse("1 2")),
+            (1, Rule::parse("\"a\"")),
+            (2, Rule::parse("1 | 1 2")),
+        ]);
+        let dot = dot(&rules).to_string();
+        assert!(dot.contains("n0 [label=\"0: 1 2\", style=filled, fillcolor=\"gold\"];"));
+        assert!(dot.contains("n2 [label=\"2: 1 | 1 2\", style=filled, fillcolor=\"lightcoral\"];"));
+        assert!(dot.contains("n0 -> n2;\n"));
+        assert!(dot.contains("n2 -> n1 [label=\"1\"];"));
+        assert!(dot.contains("n2 -> n2 [label=\"2\"];"));
+    }
+}
@@ -1,35 +1,20 @@
 use ed2020 as base;
 
+use aoc::{dot::Dot, graph::Graph};
+
+/// Graph of bag colors, with an edge for each bag contained by another,
+/// weighted by the amount of bags
 #[derive(Debug)]
-struct BagRules {
-    colors: Vec<String>,
-    contains: Vec<Vec<usize>>,
-    amount: Vec<Vec<usize>>,
-}
+struct BagRules(Graph<usize>);
 
 impl BagRules {
     fn new() -> Self {
-        Self {
-            colors: Vec::new(),
-            contains: Vec::new(),
-            amount: Vec::new(),
-        }
-    }
-
-    fn add_bag(&mut self, color: &str) -> usize {
-        if let Some(idx) = self.colors.iter().position(|c| c == color) {
-            idx
-        } else {
-            self.colors.push(color.to_string());
-            self.contains.push(Vec::new());
-            self.amount.push(Vec::new());
-            self.colors.len() - 1
-        }
+        Self(Graph::new())
     }
 
     fn add_rule(&mut self, rule: &str) -> Option<()> {
         let mut split = rule.split(" bags contain ");
-        let idx = self.add_bag(split.next()?);
+        let idx = self.0.node(split.next()?);
         let contain = split.next()?.split(" bag");
         for mut color in contain {
             if color == "no other" || color.starts_with('.') || color.starts_with("s.") {
@@ -41,68 +26,57 @@ impl BagRules {
             }
             let start = color.chars().position(|c| c == ' ')?;
 
-            self.amount[idx].push(color[..start].parse().ok()?);
+            let amount = color[..start].parse().ok()?;
 
             color = &color[start + 1..];
 
-            let idxc = self.add_bag(color);
-            self.contains[idx].push(idxc);
+            let idxc = self.0.node(color);
+            self.0.add_edge(idx, idxc, amount);
         }
 
         Some(())
     }
 
     fn nested_bags(&self, color: &str) -> usize {
-        if let Some(idx) = self.colors.iter().position(|c| c == color) {
-            let mut bags = 0;
-            let mut add = vec![(1, idx)];
-            while !add.is_empty() {
-                let (amount, idx) = add.pop().unwrap();
-                bags += amount * self.amount[idx].iter().sum::<usize>();
-                for (i, a) in self.contains[idx].iter().zip(self.amount[idx].iter()) {
-                    add.push((a * amount, *i))
-                }
-            }
-            bags
-        } else {
-            0
-        }
+        let Some(idx) = self.0.index(color) else {
+            return 0;
+        };
+        let nested = self.0.aggregate(|_, contains| {
+            contains
+                .iter()
+                .map(|(&amount, &nested)| amount * (1 + nested))
+                .sum()
+        });
+        nested.unwrap()[idx]
     }
 
     fn which_contains(&self, color: &str) -> usize {
-        if let Some(idx) = self.colors.iter().position(|c| c == color) {
-            let mut sum = 0;
-            let mut counted = Vec::new();
-            let mut count = vec![idx];
-            while !count.is_empty() {
-                let idx = count.pop().unwrap();
-                for (i, v) in self.contains.iter().enumerate() {
-                    if !counted.contains(&i) && v.contains(&idx) {
-                        counted.push(i);
-                        count.push(i);
-                        sum += 1;
-                    }
-                }
-            }
-            sum
-        } else {
-            0
+        let Some(idx) = self.0.index(color) else {
+            return 0;
+        };
+        self.0.reaching(idx).into_iter().filter(|&r| r).count() - 1
+    }
+
+    /// Graph of the rules where the bag of the given color is highlighted
+    /// along with the ones that can contain it
+    fn dot(&self, color: &str) -> Dot {
+        let mut dot = Dot::from_graph(&self.0);
+        if let Some(idx) = self.0.index(color) {
+            let reaching = self.0.reaching(idx).into_iter().enumerate();
+            dot.highlight(reaching.filter_map(|(i, r)| r.then_some(i)), "lightblue");
+            dot.highlight([idx], "gold");
         }
+        dot
     }
 }
 
 use std::fmt;
 impl fmt::Display for BagRules {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for ((color, vec), ams) in self
-            .colors
-            .iter()
-            .zip(self.contains.iter())
-            .zip(self.amount.iter())
-        {
-            write!(f, "{} contains ", color)?;
-            for (color, amount) in vec.iter().map(|idx| &self.colors[*idx]).zip(ams.iter()) {
-                write!(f, "{} {}, ", amount, color)?;
+        for idx in 0..self.0.len() {
+            write!(f, "{} contains ", self.0.name(idx))?;
+            for &(color, amount) in self.0.edges(idx) {
+                write!(f, "{} {}, ", amount, self.0.name(color))?;
             }
             writeln!(f)?;
         }
@@ -117,7 +91,10 @@ fn main() {
     for line in input.lines() {
         rules.add_rule(line);
     }
-    // println!("{}", rules);
+    if std::env::args().any(|arg| arg == "--dot") {
+        print!("{}", rules.dot("shiny gold"));
+        return;
+    }
 
     let count = rules.which_contains("shiny gold");
     let bags = rules.nested_bags("shiny gold");
@@ -1,5 +1,7 @@
 use ed2020 as base;
 
+use aoc::vm::{Cfg, Halt, Instruction, Vm};
+
 #[derive(Copy, Clone, Debug)]
 enum Cmd {
     Nop(i32),
@@ -8,13 +10,27 @@ enum Cmd {
 }
 
 impl Cmd {
-    pub fn execute(self, prg: &mut Program) {
-        match self {
-            Cmd::Nop(_) => (),
-            Cmd::Jmp(v) => prg.ip += v - 1,
-            Cmd::Acc(v) => prg.acc += v,
+    /// Swaps a `nop` with a `jmp` and viceversa, returns false for `acc`
+    fn flip(&mut self) -> bool {
+        match *self {
+            Cmd::Nop(v) => *self = Cmd::Jmp(v),
+            Cmd::Jmp(v) => *self = Cmd::Nop(v),
+            Cmd::Acc(_) => return false,
+        }
+        true
+    }
+}
+
+impl Instruction for Cmd {
+    fn execute(&self, acc: &mut i64) -> isize {
+        match *self {
+            Cmd::Nop(_) => 1,
+            Cmd::Jmp(v) => v as isize,
+            Cmd::Acc(v) => {
+                *acc += v as i64;
+                1
+            }
         }
-        prg.ip += 1;
     }
 }
 
@@ -31,80 +47,33 @@ impl std::str::FromStr for Cmd {
     }
 }
 
-struct Program {
-    cmds: Vec<Cmd>,
-    flags: Vec<bool>,
-    acc: i32,
-    ip: i32,
-}
+struct Program(Vm<Cmd>);
 
 impl Program {
-    fn new() -> Self {
-        Self {
-            cmds: Vec::new(),
-            flags: Vec::new(),
-            acc: 0,
-            ip: 0,
-        }
-    }
-
-    fn reset(&mut self) {
-        self.ip = 0;
-        self.acc = 0;
-        self.flags.iter_mut().for_each(|f| *f = false);
-    }
-
-    fn add_cmd(&mut self, cmd: Cmd) {
-        self.cmds.push(cmd);
-        self.flags.push(false);
-    }
-
     fn execute(&mut self) -> bool {
-        while self.ip >= 0 && (self.ip as usize) < self.cmds.len() && !self.flags[self.ip as usize]
-        {
-            self.flags[self.ip as usize] = true;
-            self.cmds[self.ip as usize].execute(self);
-        }
-        self.ip as usize == self.cmds.len()
+        self.0.reset();
+        self.0.run() == Halt::Terminated
     }
 
-    fn fix(&mut self) {
-        let mut change = 0;
-        while {
-            if let Some(offset) = self.cmds[change..]
-                .iter()
-                .position(|c| matches!(c, Cmd::Jmp(_) | Cmd::Nop(_)))
-            {
-                change += offset;
-                match self.cmds[change] {
-                    Cmd::Nop(v) => self.cmds[change] = Cmd::Jmp(v),
-                    Cmd::Jmp(v) => self.cmds[change] = Cmd::Nop(v),
-                    _ => unreachable!(),
-                }
-                !self.execute()
-            } else {
-                false
-            }
-        } {
-            self.reset();
-            match self.cmds[change] {
-                Cmd::Nop(v) => self.cmds[change] = Cmd::Jmp(v),
-                Cmd::Jmp(v) => self.cmds[change] = Cmd::Nop(v),
-                _ => unreachable!(),
-            }
-            change += 1;
-        }
+    /// Flips the only `nop` or `jmp` that makes the program terminate
+    fn fix(&mut self) -> Option<usize> {
+        let program = self.0.program();
+        let change = Cfg::new(program).repair(|ip| match program[ip] {
+            Cmd::Nop(v) => Some(v as isize),
+            Cmd::Jmp(_) => Some(1),
+            Cmd::Acc(_) => None,
+        })?;
+        self.0.program_mut()[change].flip();
+        self.execute();
+        Some(change)
     }
 }
 
 impl std::str::FromStr for Program {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut prg = Self::new();
-        for line in s.lines() {
-            prg.add_cmd(line.parse()?);
-        }
-        Ok(prg)
+        let cmds = s.lines().map(str::parse).collect::<Result<_, _>>()?;
+        Ok(Self(Vm::new(cmds)))
     }
 }
 
@@ -112,9 +81,19 @@ fn main() {
     let input = base::get_input(8).unwrap();
 
     let mut prg: Program = input.parse().unwrap();
+    let cfg = Cfg::new(prg.0.program());
+    if std::env::args().any(|arg| arg == "--dot") {
+        print!("{}", cfg.dot(|ip| format!("{:?}", prg.0.program()[ip])));
+        return;
+    }
+
     prg.execute();
-    println!("Acc: {}", prg.acc);
+    println!("Acc: {}", prg.0.acc());
+    if let (_, Halt::Loop(cycle)) = cfg.path() {
+        println!("Loops through: {:?}", cycle.ips);
+    }
 
-    prg.fix();
-    println!("Acc: {}", prg.acc);
+    let change = prg.fix().unwrap();
+    println!("Fixed: {} ({:?})", change, prg.0.program()[change]);
+    println!("Acc: {}", prg.0.acc());
 }
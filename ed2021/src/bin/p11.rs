@@ -1,7 +1,16 @@
-use std::iter::{repeat, successors};
+use std::iter::repeat_with;
+
+use aoc::flood::{Connectivity, Grid};
 
 const INPUT: &str = include_str!("../../assets/p11.in");
 
+/// Width and height of the map
+fn dimensions(input: &str) -> (usize, usize) {
+    let width = input.lines().next().map_or(0, str::len);
+    (width, input.lines().count())
+}
+
+/// Energy levels on the stack, for maps whose size is known at compile time
 fn get_map<const WIDTH: usize, const HEIGHT: usize>(input: &str) -> [[u8; WIDTH]; HEIGHT] {
     input
         .lines()
@@ -15,79 +24,138 @@ fn get_map<const WIDTH: usize, const HEIGHT: usize>(input: &str) -> [[u8; WIDTH]
         .1
 }
 
-fn neighbours<const WIDTH: usize, const HEIGHT: usize>(
-    (x, y): (usize, usize),
-) -> [Option<(usize, usize)>; 8] {
-    [
-        Some(x).zip(Some(y + 1).filter(|&y| y < 10)),
-        x.checked_sub(1).zip(Some(y + 1).filter(|&y| y < 10)),
-        x.checked_sub(1).zip(Some(y)),
-        x.checked_sub(1).zip(y.checked_sub(1)),
-        Some(x).zip(y.checked_sub(1)),
-        Some(x + 1).filter(|&x| x < 10).zip(y.checked_sub(1)),
-        Some(x + 1).filter(|&x| x < 10).zip(Some(y)),
-        Some((x + 1, y + 1)).filter(|&(x, y)| x < 10 && y < 10),
-    ]
+/// Energy levels of a map of any size, row by row
+fn get_levels(input: &str) -> Vec<u8> {
+    input
+        .lines()
+        .flat_map(str::bytes)
+        .map(|v| v - b'0')
+        .collect()
+}
+
+/// Octopuses whose flashes are simulated one step at a time
+trait Octopuses {
+    /// Advances the octopuses by one step and returns how many flashed
+    fn step(&mut self) -> usize;
+
+    fn len(&self) -> usize;
+}
+
+/// Map whose size is known at compile time, so that the bounds of the
+/// neighbours are constants
+struct SizedMap<const WIDTH: usize, const HEIGHT: usize> {
+    levels: [[u8; WIDTH]; HEIGHT],
+    flashing: Vec<(usize, usize)>,
 }
 
-fn indices<const WIDTH: usize, const HEIGHT: usize>() -> impl Iterator<Item = (usize, usize)> + Clone
-{
-    (0..WIDTH).flat_map(|x| repeat(x).zip(0..HEIGHT))
+impl<const WIDTH: usize, const HEIGHT: usize> SizedMap<WIDTH, HEIGHT> {
+    fn new(levels: [[u8; WIDTH]; HEIGHT]) -> Self {
+        Self {
+            levels,
+            flashing: vec![],
+        }
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Octopuses for SizedMap<WIDTH, HEIGHT> {
+    fn step(&mut self) -> usize {
+        let levels = &mut self.levels;
+        for (y, row) in levels.iter_mut().enumerate() {
+            for (x, level) in row.iter_mut().enumerate() {
+                *level += 1;
+                if *level > 9 {
+                    self.flashing.push((x, y));
+                }
+            }
+        }
+        // every octopus flashes when its level goes past 9, so only once
+        while let Some((x, y)) = self.flashing.pop() {
+            let rows = y.saturating_sub(1)..=(y + 1).min(HEIGHT - 1);
+            let cols = x.saturating_sub(1)..=(x + 1).min(WIDTH - 1);
+            let around = rows.flat_map(|ny| cols.clone().map(move |nx| (nx, ny)));
+            for (nx, ny) in around.filter(|&n| n != (x, y)) {
+                let level = &mut levels[ny][nx];
+                *level += 1;
+                if *level == 10 {
+                    self.flashing.push((nx, ny));
+                }
+            }
+        }
+        let flashed = levels.as_flattened_mut().iter_mut().filter(|v| **v > 9);
+        flashed.map(|v| *v = 0).count()
+    }
+
+    fn len(&self) -> usize {
+        WIDTH * HEIGHT
+    }
+}
+
+/// Map of any size, with the energy levels row by row
+struct Map {
+    levels: Vec<u8>,
+    grid: Grid,
+    flashing: Vec<(usize, usize)>,
+}
+
+impl Map {
+    fn new(input: &str) -> Self {
+        let (width, height) = dimensions(input);
+        Self {
+            levels: get_levels(input),
+            grid: Grid::new(width, height, Connectivity::Eight),
+            flashing: vec![],
+        }
+    }
+}
+
+impl Octopuses for Map {
+    fn step(&mut self) -> usize {
+        let (levels, grid) = (&mut self.levels, self.grid);
+        levels.iter_mut().for_each(|v| *v += 1);
+        let flashing = grid.cells().filter(|&c| levels[grid.index(c)] > 9);
+        self.flashing.extend(flashing);
+        // every octopus flashes when its level goes past 9, so only once
+        while let Some(cell) = self.flashing.pop() {
+            for n in grid.neighbours(cell) {
+                let level = &mut levels[grid.index(n)];
+                *level += 1;
+                if *level == 10 {
+                    self.flashing.push(n);
+                }
+            }
+        }
+        let flashed = levels.iter_mut().filter(|v| **v > 9);
+        flashed.map(|v| *v = 0).count()
+    }
+
+    fn len(&self) -> usize {
+        self.levels.len()
+    }
+}
+
+/// Calls `f` with the octopuses of the map, using the map sized at compile
+/// time for the size of the puzzle maps
+fn with_octopuses<T>(input: &str, f: impl FnOnce(&mut dyn Octopuses) -> T) -> T {
+    match dimensions(input) {
+        (10, 10) => f(&mut SizedMap::new(get_map::<10, 10>(input))),
+        _ => f(&mut Map::new(input)),
+    }
+}
+
+/// Number of flashes at each step
+fn flashes(octopuses: &mut dyn Octopuses) -> impl Iterator<Item = usize> + '_ {
+    repeat_with(move || octopuses.step())
 }
 
 fn solve_1(input: &str) -> usize {
-    let map = get_map::<10, 10>(input);
-    successors(Some((0, map)), |(accum, map)| {
-        let map = map.map(|row| row.map(|i| i + 1));
-        let flashed = [[false; 10]; 10];
-        let (flashes, map, _) = successors(Some((0, map, flashed)), |(prev, mut map, flashed)| {
-            let flashing = map.map(|row| row.map(|v| v > 9));
-            let flashes = indices::<10, 10>()
-                .filter(|&(x, y)| flashing[x][y] && !flashed[x][y])
-                .count();
-            indices::<10, 10>()
-                .filter(|&(x, y)| flashing[x][y] && !flashed[x][y])
-                .flat_map(neighbours::<10, 10>)
-                .flatten()
-                .map(|(x, y)| map[x][y] += 1)
-                .count();
-            (flashes > 0).then(|| (prev + flashes, map, flashing))
-        })
-        .last()
-        .unwrap();
-        let map = map.map(|row| row.map(|v| (v > 9).then(|| 0).unwrap_or(v)));
-        Some((accum + flashes, map))
-    })
-    .take(100 + 1)
-    .last()
-    .unwrap()
-    .0
+    with_octopuses(input, |octopuses| flashes(octopuses).take(100).sum())
 }
 
 fn solve_2(input: &str) -> usize {
-    let map = get_map::<10, 10>(input);
-    successors(Some(map), |map| {
-        let map = map.map(|row| row.map(|i| i + 1));
-        let flashed = [[false; 10]; 10];
-        let (map, flashing) = successors(Some((map, flashed)), |&(mut map, flashed)| {
-            let flashing = map.map(|row| row.map(|v| v > 9));
-            indices::<10, 10>()
-                .filter(|&(x, y)| flashing[x][y] && !flashed[x][y])
-                .flat_map(neighbours::<10, 10>)
-                .flatten()
-                .map(|(x, y)| map[x][y] += 1)
-                .count();
-            (flashing != flashed).then(|| (map, flashing))
-        })
-        .last()
-        .unwrap();
-        let map = map.map(|row| row.map(|v| (v > 9).then(|| 0).unwrap_or(v)));
-        Some(map).filter(|_| flashing != [[true; 10]; 10])
+    with_octopuses(input, |octopuses| {
+        let all = octopuses.len();
+        flashes(octopuses).position(|f| f == all).unwrap() + 1
     })
-    .enumerate()
-    .last()
-    .unwrap()
-    .0 + 1
 }
 
 fn main() {
@@ -115,4 +183,32 @@ mod tests {
         assert_eq!(super::solve_1(TEST_INPUT), 1656);
         assert_eq!(super::solve_2(TEST_INPUT), 195);
     }
+
+    #[test]
+    fn any_size() {
+        use super::{dimensions, flashes, get_levels, get_map, Map, SizedMap};
+
+        let input = "11111\n19991\n19191\n19991\n11111\n";
+        assert_eq!(dimensions(input), (5, 5));
+        let mut map = Map::new(input);
+        let counts: Vec<_> = flashes(&mut map).take(2).collect();
+        assert_eq!(counts, [9, 0]);
+        assert_eq!(
+            map.levels,
+            get_levels("45654\n51115\n61116\n51115\n45654\n")
+        );
+
+        // the same map without the last column, on both kinds of maps
+        let narrow: String = TEST_INPUT
+            .lines()
+            .map(|l| format!("{}\n", &l[..9]))
+            .collect();
+        let mut map = Map::new(&narrow);
+        let mut sized = SizedMap::new(get_map::<9, 10>(&narrow));
+        assert_eq!(map.levels, sized.levels.as_flattened());
+        let steps = flashes(&mut map).take(100).sum::<usize>();
+        assert_eq!(flashes(&mut sized).take(100).sum::<usize>(), steps);
+        assert_eq!(map.levels, sized.levels.as_flattened());
+        assert_eq!(super::solve_1(&narrow), steps);
+    }
 }
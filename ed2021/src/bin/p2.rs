@@ -1,79 +1,34 @@
+use aoc::{
+    geometry::Point,
+    turtle::{self, Action, Command, Heading, Language, Turtle, Waypoint},
+};
+
 const INPUT: &str = include_str!("../../assets/p2.in");
 
-#[derive(Default)]
-struct Submarine {
-    depth: usize,
-    pos: usize,
-    aim: usize,
+/// Commands of the submarine, with the depth growing downwards
+fn commands(input: &str) -> Vec<Command> {
+    let language = Language::new(&[
+        ("forward", Action::Forward),
+        ("down", Action::Move(Point::new(0, 1))),
+        ("up", Action::Move(Point::new(0, -1))),
+    ]);
+    language.parse_all(input).unwrap()
 }
 
-impl Submarine {
-    fn new() -> Self {
-        Self::default()
-    }
-
-    fn move_down(&mut self, val: usize) {
-        self.depth -= val
-    }
-
-    fn move_up(&mut self, val: usize) {
-        self.depth += val
-    }
-
-    fn aim_up(&mut self, val: usize) {
-        self.aim -= val
-    }
-
-    fn aim_down(&mut self, val: usize) {
-        self.aim += val
-    }
-
-    fn move_forward(&mut self, val: usize) {
-        self.pos += val;
-        self.depth += self.aim * val;
-    }
-
-    fn position(&self) -> usize {
-        self.depth * self.pos
-    }
-
-    fn exec(
-        &mut self,
-        cmd: &str,
-        val: usize,
-        forward: impl Fn(&mut Self, usize),
-        down: impl Fn(&mut Self, usize),
-        up: impl Fn(&mut Self, usize),
-    ) {
-        match cmd {
-            "forward" => forward(self, val),
-            "down" => up(self, val),
-            "up" => down(self, val),
-            s => unreachable!(s),
-        }
-    }
+/// Product of the horizontal position and the depth at the end
+fn position(input: &str, mut submarine: impl Turtle) -> i64 {
+    turtle::run(&mut submarine, &commands(input));
+    let end = submarine.position();
+    end.x * end.y
 }
 
-use Submarine as Sub;
-
-fn solve_1(input: &str) -> usize {
-    let mut sub = Sub::new();
-    for line in input.lines() {
-        let (cmd, n) = line.split_once(' ').unwrap();
-        let n = n.parse().unwrap();
-        sub.exec(cmd, n, Sub::move_forward, Sub::move_up, Sub::move_down);
-    }
-    sub.position()
+fn solve_1(input: &str) -> i64 {
+    position(input, Heading::new(Point::new(0, 0), Point::new(1, 0)))
 }
 
-fn solve_2(input: &str) -> usize {
-    let mut sub = Sub::new();
-    for line in input.lines() {
-        let (cmd, n) = line.split_once(' ').unwrap();
-        let n: usize = n.parse().unwrap();
-        sub.exec(cmd, n, Sub::move_forward, Sub::aim_up, Sub::aim_down);
-    }
-    sub.position()
+/// The aim is a waypoint one step ahead, that `down` and `up` move
+fn solve_2(input: &str) -> i64 {
+    position(input, Waypoint::new(Point::new(0, 0), Point::new(1, 0)))
 }
 
 fn main() {
@@ -1,62 +1,114 @@
+use aoc::bits::{BitMatrix, Bits};
+
 const INPUT: &str = include_str!("../../assets/p3.in");
 
-fn solve_1<const SIZE: usize>(input: &str) -> usize {
-    let mut total = 0;
-    let mut ones = [0; SIZE];
-    for line in input.lines() {
-        total += 1;
-        line.bytes()
-            .enumerate()
-            .filter_map(|(i, v)| (v == b'1').then(|| i))
-            .for_each(|i| ones[i] += 1);
-    }
-    let half = total / 2;
-    let gamma = ones
+fn report(input: &str) -> BitMatrix {
+    input.parse().expect("lines of 0 and 1 of the same width")
+}
+
+/// Gamma and epsilon rates, from the most and least common bits
+fn rates(report: &BitMatrix) -> [Bits; 2] {
+    let gamma: Bits = report
+        .column_ones()
         .into_iter()
-        .fold(0, |res, ones| (res << 1) + (ones > half) as usize);
-    let epsilon = !gamma & ((1 << SIZE) - 1);
-    gamma * epsilon
+        .map(|ones| ones * 2 > report.height())
+        .collect();
+    let epsilon = gamma.not();
+    [gamma, epsilon]
 }
 
-fn solve_2<const SIZE: usize>(input: &str) -> usize {
-    let mut oxy = [0; SIZE];
-    let mut co2 = [0; SIZE];
+/// Oxygen generator and CO2 scrubber ratings, read from a single trie of the
+/// report
+fn ratings(report: &BitMatrix) -> [Bits; 2] {
+    let trie = report.trie();
+    let oxygen = trie.select(|zeros, ones| ones >= zeros);
+    let co2 = trie.select(|zeros, ones| ones < zeros);
+    [oxygen, co2]
+}
 
-    for i in 0..SIZE {
-        let mut oxy_total = 0;
-        let mut co2_total = 0;
+/// Product of the two values, if it fits
+fn product([a, b]: [Bits; 2]) -> Option<u128> {
+    a.to_u128()?.checked_mul(b.to_u128()?)
+}
 
-        let mut oxy_count = 0;
-        let mut co2_count = 0;
+/// Power consumption and life support rating of a report of any width
+fn solve_any(input: &str) -> (u128, u128) {
+    let report = report(input);
+    let power = product(rates(&report)).expect("power consumption overflow");
+    let life_support = product(ratings(&report)).expect("life support rating overflow");
+    (power, life_support)
+}
 
-        for line in input.lines() {
-            let (prefix, n) = line.as_bytes().split_at(i);
-            let oxy_prefix = prefix == &oxy[..i];
-            let co2_prefix = prefix == &co2[..i];
+/// Rating found by narrowing the sorted numbers bit by bit, where the ones
+/// starting with the same bits are next to each other like in the trie,
+/// `keep_ones` chooses between the numbers with a zero and with a one
+fn rating<const WIDTH: usize>(sorted: &[u64], keep_ones: impl Fn(usize, usize) -> bool) -> u64 {
+    let (mut lo, mut hi) = (0, sorted.len());
+    for bit in (0..WIDTH).rev() {
+        if hi - lo <= 1 {
+            break;
+        }
+        let split = lo + sorted[lo..hi].partition_point(|v| v >> bit & 1 == 0);
+        let (zeros, ones) = (split - lo, hi - split);
+        match zeros == 0 || (ones != 0 && keep_ones(zeros, ones)) {
+            true => lo = split,
+            false => hi = split,
+        }
+    }
+    sorted[lo]
+}
 
-            oxy_total += oxy_prefix as usize;
-            co2_total += co2_prefix as usize;
+/// Same as `solve_any` for a width known at compile time, with the numbers
+/// packed in a word and the counts of ones on the stack
+fn solve_sized<const WIDTH: usize>(input: &str) -> (u128, u128) {
+    let mut ones = [0; WIDTH];
+    let mut values: Vec<u64> = input
+        .lines()
+        .map(|line| {
+            let bits = <&[u8; WIDTH]>::try_from(line.as_bytes()).expect("rows of the same width");
+            bits.iter().zip(&mut ones).fold(0, |value, (&b, ones)| {
+                let bit = match b {
+                    b'0' => 0,
+                    b'1' => 1,
+                    _ => panic!("lines of 0 and 1"),
+                };
+                *ones += bit;
+                value << 1 | bit as u64
+            })
+        })
+        .collect();
+    let gamma = ones.iter().fold(0, |gamma, &ones| {
+        gamma << 1 | (ones * 2 > values.len()) as u64
+    });
+    let epsilon = !gamma & ((1 << WIDTH) - 1);
 
-            oxy_count += (oxy_prefix && n[0] == b'1') as usize;
-            co2_count += (co2_prefix && n[0] == b'1') as usize;
-        }
-        oxy[i] = b'0'
-            + ((oxy_total == 1 && oxy_count == 1) || (oxy_total > 1 && oxy_count * 2 >= oxy_total))
-                as u8;
-        co2[i] = b'0'
-            + ((co2_total == 1 && co2_count == 1) || (co2_total > 1 && co2_count * 2 < co2_total))
-                as u8;
+    values.sort_unstable();
+    let oxygen = rating::<WIDTH>(&values, |zeros, ones| ones >= zeros);
+    let co2 = rating::<WIDTH>(&values, |zeros, ones| ones < zeros);
+    let product = |a: u64, b: u64| a as u128 * b as u128;
+    (product(gamma, epsilon), product(oxygen, co2))
+}
+
+/// Uses the sized path for the widths of the example and of the real input
+fn solve(input: &str) -> (u128, u128) {
+    match input.lines().next().map_or(0, str::len) {
+        5 => solve_sized::<5>(input),
+        12 => solve_sized::<12>(input),
+        _ => solve_any(input),
     }
-    let oxy = oxy.map(|v| v - b'0').map(usize::from);
-    let co2 = co2.map(|v| v - b'0').map(usize::from);
-    let oxygen_gen = oxy.into_iter().fold(0, |res, bit| res * 2 + bit);
-    let co2_scrub = co2.into_iter().fold(0, |res, bit| res * 2 + bit);
-    oxygen_gen * co2_scrub
+}
+
+fn solve_1(input: &str) -> u128 {
+    solve(input).0
+}
+
+fn solve_2(input: &str) -> u128 {
+    solve(input).1
 }
 
 fn main() {
-    println!("Answer 1: {}", solve_1::<12>(INPUT));
-    println!("Answer 2: {}", solve_2::<12>(INPUT));
+    println!("Answer 1: {}", solve_1(INPUT));
+    println!("Answer 2: {}", solve_2(INPUT));
 }
 
 #[cfg(test)]
@@ -66,7 +118,31 @@ mod tests {
 
     #[test]
     fn test() {
-        assert_eq!(super::solve_1::<5>(TEST_INPUT), 198);
-        assert_eq!(super::solve_2::<5>(TEST_INPUT), 230);
+        assert_eq!(super::solve_1(TEST_INPUT), 198);
+        assert_eq!(super::solve_2(TEST_INPUT), 230);
+    }
+
+    #[test]
+    fn any_width() {
+        use super::{ratings, report, solve_1, solve_2, solve_any, solve_sized, INPUT};
+
+        assert_eq!(solve_any(TEST_INPUT), (198, 230));
+        assert_eq!(solve_sized::<5>(TEST_INPUT), (198, 230));
+        assert_eq!(solve_sized::<12>(INPUT), solve_any(INPUT));
+
+        // the example with a zero in front of every number
+        let wider: String = TEST_INPUT.lines().map(|l| format!("0{l}\n")).collect();
+        assert_eq!(solve_1(&wider), 22 * 41);
+
+        // wider than 64 bits
+        let wide: String = TEST_INPUT
+            .lines()
+            .map(|l| format!("{}{l}\n", "0".repeat(95)))
+            .collect();
+        let epsilon = ((1u128 << 95) - 1) << 5 | 9;
+        assert_eq!(solve_1(&wide), 22 * epsilon);
+        assert_eq!(solve_2(&wide), 230);
+        let [oxygen, _] = ratings(&report(&wide));
+        assert_eq!(oxygen.to_string(), format!("{}10111", "0".repeat(95)));
     }
 }
@@ -1,100 +1,210 @@
-use std::str::FromStr;
+use std::{collections::HashMap, fmt, num::ParseIntError, str::FromStr};
 
 const INPUT: &str = include_str!("../../assets/p4.in");
 
-macro_rules! impl_iter {
-    ($ty:ty) => { impl Iterator<Item = $ty> + Clone };
+/// Square board of any size that keeps track of how many numbers are marked
+/// on each line, so that a win is detected as soon as it happens
+#[derive(Clone, Debug)]
+struct Board {
+    size: usize,
+    nums: Vec<usize>,
+    marked: Vec<bool>,
+    /// Marked numbers on each row, column, and the two diagonals
+    rows: Vec<usize>,
+    cols: Vec<usize>,
+    diags: [usize; 2],
+    unmarked_sum: usize,
 }
 
-pub fn max_opt(a: Option<usize>, b: Option<usize>) -> Option<usize> {
-    a.zip(b).map(|(a, b)| a.max(b))
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ParseError {
+    Number(ParseIntError),
+    /// A row of a board, counted from 1, without as many numbers as the rows
+    NotSquare {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
 }
 
-struct Board<'a> {
-    nums: [[&'a str; 5]; 5],
+impl From<ParseIntError> for ParseError {
+    fn from(err: ParseIntError) -> Self {
+        ParseError::Number(err)
+    }
 }
 
-impl<'a> Board<'a> {
-    pub fn new(numbers: &'a str) -> Self {
-        let mut nums = [[""; 5]; 5];
-        numbers
-            .split_ascii_whitespace()
-            .zip(nums.iter_mut().flatten())
-            .for_each(|(n, m)| *m = n);
-        Self { nums }
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Number(err) => write!(f, "invalid number, {err}"),
+            ParseError::NotSquare {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row {row} of a board has {found} numbers instead of {expected}"
+            ),
+        }
     }
+}
+
+impl FromStr for Board {
+    type Err = ParseError;
 
-    pub fn check_win<'b>(&'a self, numbers: impl_iter!(&'b str)) -> Option<usize> {
-        match (self.check_win_h(numbers.clone()), self.check_win_v(numbers)) {
-            (Some(a), Some(b)) => Some(a.min(b)),
-            (v @ Some(_), None) | (None, v @ Some(_)) => v,
-            (None, None) => None,
+    /// Parses a board with a line for each row, its size is the number of rows
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<_> = s.lines().filter(|l| !l.trim().is_empty()).collect();
+        let size = lines.len();
+        let mut nums = Vec::with_capacity(size * size);
+        for (line, row) in lines.into_iter().zip(1..) {
+            let len = nums.len();
+            for n in line.split_ascii_whitespace() {
+                nums.push(n.parse()?);
+            }
+            if nums.len() - len != size {
+                return Err(ParseError::NotSquare {
+                    row,
+                    expected: size,
+                    found: nums.len() - len,
+                });
+            }
         }
+        Ok(Self {
+            size,
+            marked: vec![false; nums.len()],
+            unmarked_sum: nums.iter().sum(),
+            nums,
+            rows: vec![0; size],
+            cols: vec![0; size],
+            diags: [0; 2],
+        })
     }
+}
 
-    pub fn check_win_h<'b>(&'a self, numbers: impl_iter!(&'b str)) -> Option<usize> {
-        self.nums
-            .iter()
-            .map(|row| row.map(|n| numbers.clone().position(|m| n == m)))
-            .filter_map(|row| row.into_iter().fold(Some(0), max_opt))
-            .min()
+impl Board {
+    /// Marks the cell at the given index, returns whether that completed a
+    /// row, a column or, if `diagonals` is set, one of the two diagonals
+    fn mark(&mut self, cell: usize, diagonals: bool) -> bool {
+        if std::mem::replace(&mut self.marked[cell], true) {
+            return false;
+        }
+        self.unmarked_sum -= self.nums[cell];
+        let (r, c) = (cell / self.size, cell % self.size);
+        self.rows[r] += 1;
+        self.cols[c] += 1;
+        let mut full = self.rows[r] == self.size || self.cols[c] == self.size;
+        for (d, on) in [r == c, r + c + 1 == self.size].into_iter().enumerate() {
+            if on {
+                self.diags[d] += 1;
+                full |= diagonals && self.diags[d] == self.size;
+            }
+        }
+        full
     }
+}
+
+/// A board getting its first complete line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Win {
+    board: usize,
+    /// Index of the draw, starting from 1
+    draw: usize,
+    score: usize,
+}
 
-    pub fn check_win_v<'b>(&'a self, numbers: impl_iter!(&'b str)) -> Option<usize> {
-        (0..5)
-            .map(|i| {
-                self.nums
-                    .map(|row| numbers.clone().position(|n| n == row[i]))
-            })
-            .filter_map(|row| row.into_iter().fold(Some(0), max_opt))
-            .min()
+impl fmt::Display for Win {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Win { board, draw, score } = self;
+        write!(f, "board {board} wins on draw {draw} with score {score}")
     }
+}
+
+struct Bingo {
+    draws: Vec<usize>,
+    boards: Vec<Board>,
+    /// Board and cell index of every occurrence of each number
+    cells: HashMap<usize, Vec<(usize, usize)>>,
+    /// Whether completing a diagonal also wins
+    diagonals: bool,
+}
+
+impl FromStr for Bingo {
+    type Err = ParseError;
 
-    pub fn sum_unmarked<'b>(&'a self, numbers: impl_iter!(&'b str)) -> usize {
-        self.nums
-            .into_iter()
-            .flatten()
-            .filter(|&n| numbers.clone().all(|m| n != m))
-            .map(usize::from_str)
-            .map(Result::unwrap)
-            .sum()
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let eol = if s.contains("\r\n") {
+            "\r\n\r\n"
+        } else {
+            "\n\n"
+        };
+        let (first, rest) = s.split_once(eol).unwrap_or((s, ""));
+        let draws = first.trim().split(',').map(usize::from_str);
+        let draws = draws.collect::<Result<_, _>>()?;
+        let boards = rest.split(eol).filter(|b| !b.trim().is_empty());
+        let boards: Vec<Board> = boards.map(str::parse).collect::<Result<_, _>>()?;
+
+        let mut cells = HashMap::<_, Vec<_>>::new();
+        for (b, board) in boards.iter().enumerate() {
+            for (cell, &n) in board.nums.iter().enumerate() {
+                cells.entry(n).or_default().push((b, cell));
+            }
+        }
+        Ok(Self {
+            draws,
+            boards,
+            cells,
+            diagonals: false,
+        })
     }
 }
 
-fn solve<const FIRST_OR_LAST: bool>(input: &str) -> usize {
-    let (first, rest) = input.split_once("\n\n").unwrap();
-    let numbers = first.split(',');
-    let boards = rest.split("\n\n").map(Board::new);
-    let win_boards = boards
-        .clone()
-        .filter_map(|b| b.check_win(numbers.clone()).zip(Some(b)));
-
-    let (win_turn, winning) = match FIRST_OR_LAST {
-        true => win_boards.min_by_key(|(turns, _)| *turns),
-        false => win_boards.max_by_key(|(turns, _)| *turns),
+impl Bingo {
+    /// Plays the game, yielding the boards in the order they win
+    fn wins(mut self) -> impl Iterator<Item = Win> {
+        let mut won = vec![false; self.boards.len()];
+        let draws = std::mem::take(&mut self.draws).into_iter().enumerate();
+        draws.flat_map(move |(i, n)| {
+            let mut wins = vec![];
+            for &(b, cell) in self.cells.get(&n).into_iter().flatten() {
+                if !won[b] && self.boards[b].mark(cell, self.diagonals) {
+                    won[b] = true;
+                    let (draw, score) = (i + 1, self.boards[b].unmarked_sum * n);
+                    wins.push(Win {
+                        board: b,
+                        draw,
+                        score,
+                    });
+                }
+            }
+            wins
+        })
     }
-    .unwrap();
-
-    let sum: usize = winning.sum_unmarked(numbers.clone().take(win_turn + 1));
-    sum * numbers
-        .clone()
-        .nth(win_turn)
-        .unwrap()
-        .parse::<usize>()
-        .unwrap()
 }
 
 fn solve_1(input: &str) -> usize {
-    solve::<true>(input)
+    let bingo: Bingo = input.parse().unwrap();
+    bingo.wins().next().unwrap().score
 }
 
 fn solve_2(input: &str) -> usize {
-    solve::<false>(input)
+    let bingo: Bingo = input.parse().unwrap();
+    bingo.wins().last().unwrap().score
 }
 
 fn main() {
-    println!("Answer 1: {}", solve_1(INPUT));
-    println!("Answer 2: {}", solve_2(INPUT));
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        println!("Answer 1: {}", solve_1(INPUT));
+        println!("Answer 2: {}", solve_2(INPUT));
+        return;
+    }
+    // --events prints every win, --diagonals also counts the diagonals
+    let mut bingo: Bingo = INPUT.parse().unwrap();
+    bingo.diagonals = args.iter().any(|a| a == "--diagonals");
+    for win in bingo.wins() {
+        println!("{win}");
+    }
 }
 
 #[cfg(test)]
@@ -125,4 +235,50 @@ mod tests {
         assert_eq!(super::solve_1(TEST_INPUT), 4512);
         assert_eq!(super::solve_2(TEST_INPUT), 1924);
     }
+
+    #[test]
+    fn events() {
+        use super::{Bingo, Win};
+
+        let bingo: Bingo = TEST_INPUT.parse().unwrap();
+        let wins: Vec<_> = bingo.wins().collect();
+        let [board, draw, score] = [2, 12, 4512];
+        assert_eq!(wins[0], Win { board, draw, score });
+        assert_eq!(wins.len(), 3);
+        assert_eq!(
+            wins[2].to_string(),
+            "board 1 wins on draw 15 with score 1924"
+        );
+
+        // 3x3 boards, that complete a diagonal before a column
+        let input = "1,5,9,7,4\n\n1 2 3\n4 5 6\n7 8 9\n\n9 8 7\n6 5 4\n3 2 1\n";
+        let mut bingo: Bingo = input.parse().unwrap();
+        let wins: Vec<_> = bingo.wins().collect();
+        let [draw, score] = [5, (2 + 3 + 6 + 8) * 4];
+        assert_eq!(wins, [0, 1].map(|board| Win { board, draw, score }));
+
+        bingo = input.parse().unwrap();
+        bingo.diagonals = true;
+        let wins: Vec<_> = bingo.wins().map(|w| (w.board, w.draw)).collect();
+        assert_eq!(wins, [(0, 3), (1, 3)]);
+    }
+
+    #[test]
+    fn errors() {
+        use super::{Bingo, Board, ParseError};
+
+        let error = "1 2 3\n4 5\n7 8 9".parse::<Board>().unwrap_err();
+        let expected = ParseError::NotSquare {
+            row: 2,
+            expected: 3,
+            found: 2,
+        };
+        assert_eq!(error, expected);
+        assert_eq!(
+            error.to_string(),
+            "row 2 of a board has 2 numbers instead of 3"
+        );
+        let error = "1,x\n\n1\n".parse::<Bingo>().err();
+        assert!(matches!(error, Some(ParseError::Number(_))));
+    }
 }
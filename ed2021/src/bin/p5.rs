@@ -1,85 +1,31 @@
-use std::{
-    cmp::Ordering::{Equal, Greater, Less},
-    fmt::Debug,
-    str::FromStr,
-};
+use aoc::geometry::{count_overlaps, Point, Segment};
 
 const INPUT: &str = include_str!("../../assets/p5.in");
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct Point(u16, u16);
-
-impl FromStr for Point {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (a, b) = s.split_once(',').ok_or(())?;
-        Ok(Self(a.parse().or(Err(()))?, b.parse().or(Err(()))?))
-    }
+fn point(s: &str) -> Option<Point> {
+    let (x, y) = s.split_once(',')?;
+    Some(Point::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
 }
 
-#[derive(Clone, Copy)]
-struct Segment(Point, Point, bool);
-
-impl Iterator for Segment {
-    type Item = Point;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        use Segment as S;
-
-        let p = self.0;
-        match *self {
-            S(.., true) => return None,
-            S(a, b, ref mut e) if a == b => *e = true,
-            S(ref mut a, Point(x, y), _) => {
-                match a.0.cmp(&x) {
-                    Less => a.0 += 1,
-                    Greater => a.0 -= 1,
-                    Equal => (),
-                }
-                match a.1.cmp(&y) {
-                    Less => a.1 += 1,
-                    Greater => a.1 -= 1,
-                    Equal => (),
-                }
-            }
-        }
-        Some(p)
-    }
+fn segment(s: &str) -> Option<Segment> {
+    let (a, b) = s.split_once(" -> ")?;
+    Some(Segment::new(point(a)?, point(b)?))
 }
 
-impl FromStr for Segment {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (a, b) = s.split_once(" -> ").ok_or(())?;
-        Ok(Self(a.parse().or(Err(()))?, b.parse().or(Err(()))?, false))
-    }
+fn segments(input: &str) -> impl Iterator<Item = Segment> + '_ {
+    input.lines().map(segment).map(Option::unwrap)
 }
 
-fn solve(input: &str, filter: impl Fn(&Segment) -> bool) -> usize {
-    let mut points: Vec<Point> = input
-        .lines()
-        .map(Segment::from_str)
-        .map(Result::unwrap)
-        .filter(filter)
-        .flatten()
-        .collect();
-    points.sort_unstable();
-    points
-        .windows(2)
-        .filter(|v| v[0] == v[1])
-        .fold((0, None), |(count, prev), next| {
-            (count + (prev != Some(next[0])) as usize, Some(next[0]))
-        })
-        .0
+fn solve(input: &str, filter: impl Fn(&Segment) -> bool) -> u64 {
+    let segments: Vec<_> = segments(input).filter(filter).collect();
+    count_overlaps(&segments)
 }
 
-fn solve_1(input: &str) -> usize {
-    solve(input, |Segment(a, b, _)| a.0 == b.0 || a.1 == b.1)
+fn solve_1(input: &str) -> u64 {
+    solve(input, |s| s.is_horizontal() || s.is_vertical())
 }
 
-fn solve_2(input: &str) -> usize {
+fn solve_2(input: &str) -> u64 {
     solve(input, |_| true)
 }
 
@@ -107,4 +53,32 @@ mod tests {
         assert_eq!(super::solve_1(TEST_INPUT), 5);
         assert_eq!(super::solve_2(TEST_INPUT), 12);
     }
+
+    /// Counts the points of all the segments
+    fn rasterized(input: &str) -> usize {
+        let mut points: Vec<_> = super::segments(input).flat_map(|s| s.points()).collect();
+        points.sort_unstable();
+        let counts = points.chunk_by(|a, b| a == b);
+        counts.filter(|c| c.len() >= 2).count()
+    }
+
+    #[test]
+    fn any_slope() {
+        assert_eq!(
+            super::solve_2(super::INPUT),
+            rasterized(super::INPUT) as u64
+        );
+
+        let input = "\
+            0,0 -> 6,3\n\
+            2,0 -> 2,4\n\
+            0,3 -> 6,0\n\
+            4,2 -> 10,5\n\
+            1,4 -> 7,1\n";
+        assert_eq!(super::solve_2(input), rasterized(input) as u64);
+        assert_eq!(super::solve_2(input), 4);
+
+        let far = "1000000,0 -> 1000000,3000000\n0,1000000 -> 3000000,1000000\n";
+        assert_eq!(super::solve_1(far), 1);
+    }
 }
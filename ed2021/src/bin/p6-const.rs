@@ -2,52 +2,85 @@ use std::str::FromStr;
 
 const INPUT: &str = include_str!("../../assets/p6.in");
 
-struct Childs<const DAYS: usize>;
+/// Number of possible timer values
+const TIMERS: usize = 9;
 
-impl<const DAYS: usize> Childs<DAYS> {
-    const fn of_gen(gen: usize, start: usize) -> usize {
-        const fn inner(x: u128, y: u128) -> u128 {
-            let mut accum = 1;
+type Matrix = [[usize; TIMERS]; TIMERS];
+
+const fn mul(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut res = [[0; TIMERS]; TIMERS];
+    let mut r = 0;
+    while r < TIMERS {
+        let mut c = 0;
+        while c < TIMERS {
             let mut i = 0;
-            while i < x {
-                accum *= y + i;
+            while i < TIMERS {
+                res[r][c] += a[r][i] * b[i][c];
                 i += 1;
             }
-            i = 0;
-            while i < x {
-                i += 1;
-                accum /= i;
+            c += 1;
+        }
+        r += 1;
+    }
+    res
+}
+
+/// Matrix that moves the histogram of the timers one day forward
+const fn transition() -> Matrix {
+    let mut res = [[0; TIMERS]; TIMERS];
+    let mut t = 1;
+    while t < TIMERS {
+        res[t - 1][t] = 1;
+        t += 1;
+    }
+    res[6][0] = 1;
+    res[8][0] = 1;
+    res
+}
+
+struct Childs<const DAYS: usize>;
+
+impl<const DAYS: usize> Childs<DAYS> {
+    /// Transition matrix raised to the power of DAYS by repeated squaring
+    const fn matrix() -> Matrix {
+        let mut res = [[0; TIMERS]; TIMERS];
+        let mut i = 0;
+        while i < TIMERS {
+            res[i][i] = 1;
+            i += 1;
+        }
+        let mut base = transition();
+        let mut exp = DAYS;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = mul(&res, &base);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = mul(&base, &base);
             }
-            accum
         }
-        let prev = gen.saturating_sub(1);
-        let childs = (DAYS + 6).saturating_sub(start + prev * 9) / 7;
-        inner(gen as u128, childs as u128) as usize
+        res
     }
 
-    const fn all(start: usize) -> usize {
-        let mut gen = 0;
-        let mut sum = 0;
-        let mut res;
-        while {
-            res = Self::of_gen(gen, start);
-            res > 0
-        } {
-            gen += 1;
-            sum += res;
+    /// Number of fish after DAYS days starting from a single one for every
+    /// starting timer, which are the column sums of the matrix
+    const fn precompute() -> [usize; 7] {
+        let matrix = Self::matrix();
+        let mut res = [0; 7];
+        let mut start = 0;
+        while start < 7 {
+            let mut t = 0;
+            while t < TIMERS {
+                res[start] += matrix[t][start];
+                t += 1;
+            }
+            start += 1;
         }
-        sum
+        res
     }
 
-    const PRECOMPUTED: [usize; 7] = [
-        Self::all(0),
-        Self::all(1),
-        Self::all(2),
-        Self::all(3),
-        Self::all(4),
-        Self::all(5),
-        Self::all(6),
-    ];
+    const PRECOMPUTED: [usize; 7] = Self::precompute();
 }
 
 fn solve<const DAYS: usize>(input: &str) -> usize {
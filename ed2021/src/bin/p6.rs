@@ -1,41 +1,48 @@
 use std::str::FromStr;
 
+use aoc::matrix::{histogram, Matrix};
+
 const INPUT: &str = include_str!("../../assets/p6.in");
 
-fn childs_of_gen<const DAYS: usize>(gen: usize, start: usize) -> usize {
-    /// Number of childs made by the genaration x if the first child of the
-    /// previous generation made y children
-    fn inner(x: u128, y: u128) -> u128 {
-        (0..x).map(|v| v + y).product::<u128>() / (1..=x).product::<u128>()
-    }
-    let prev = gen.saturating_sub(1);
-    // number of children made by the first child of the previous generation
-    let childs = (DAYS + 6).saturating_sub(start + prev * 9) / 7;
-    usize::try_from(inner(gen as u128, childs as u128)).unwrap()
+/// Number of days after which a new fish makes its first child
+const NEW_TIMER: usize = 8;
+/// Number of days after which a fish makes another child
+const RESET_TIMER: usize = 6;
+
+/// Matrix that moves the histogram of the timers one day forward
+fn lanternfish() -> Matrix {
+    Matrix::transition(NEW_TIMER + 1, |timer| match timer {
+        0 => vec![RESET_TIMER, NEW_TIMER],
+        t => vec![t - 1],
+    })
 }
 
-/// Total number of children made in DAYS days by starting from the state start
-fn childs<const DAYS: usize>(start: usize) -> usize {
-    (0..)
-        .map(|gen| childs_of_gen::<DAYS>(gen, start))
-        .take_while(|n| n > &0)
-        .sum()
+fn timers(input: &str) -> Vec<u64> {
+    let timers = input
+        .split(',')
+        .map(str::trim)
+        .map(usize::from_str)
+        .map(Result::unwrap);
+    histogram(timers, NEW_TIMER + 1)
 }
 
-/// Precompute the number of childern made in DAYS days for every starting state
-fn precompute<const DAYS: usize>() -> [usize; 7] {
-    [0, 1, 2, 3, 4, 5, 6].map(childs::<DAYS>)
+/// Number of fish after the given days, computed modulo `modulus` if any
+fn count(input: &str, days: u64, modulus: Option<u64>) -> u64 {
+    let matrix = lanternfish();
+    let matrix = match modulus {
+        Some(m) => matrix.with_modulus(m),
+        None => matrix,
+    };
+    let fish = matrix.evolve(&timers(input), days);
+    let sum = fish.iter().map(|&n| n as u128).sum::<u128>();
+    match modulus {
+        Some(m) => (sum % m as u128) as u64,
+        None => u64::try_from(sum).unwrap(),
+    }
 }
 
 fn solve<const DAYS: usize>(input: &str) -> usize {
-    let precomputed = precompute::<DAYS>();
-    input
-        .split(',')
-        .map(str::trim)
-        .map(usize::from_str)
-        .map(Result::unwrap)
-        .map(|n| precomputed[n])
-        .sum::<usize>()
+    count(input, DAYS as u64, None) as usize
 }
 
 fn solve_1(input: &str) -> usize {
@@ -49,6 +56,9 @@ fn solve_2(input: &str) -> usize {
 fn main() {
     println!("Answer 1: {}", solve_1(INPUT));
     println!("Answer 2: {}", solve_2(INPUT));
+    const MOD: u64 = 1_000_000_007;
+    let far = count(INPUT, 10u64.pow(18), Some(MOD));
+    println!("After 10^18 days (mod {MOD}): {far}");
 }
 
 #[cfg(test)]
@@ -61,4 +71,20 @@ mod tests {
         assert_eq!(super::solve_1(TEST_INPUT), 5934);
         assert_eq!(super::solve_2(TEST_INPUT), 26984457539);
     }
+
+    #[test]
+    fn modulo() {
+        use super::{count, lanternfish, timers};
+
+        let exact = count(TEST_INPUT, 256, None);
+        for m in [7, 1_000_000_007, u64::MAX] {
+            assert_eq!(count(TEST_INPUT, 256, Some(m)), exact % m);
+        }
+        // 10^18 = (10^9)^2 days, done in two exponentiations
+        let m = 1_000_000_007;
+        let billion = lanternfish().with_modulus(m).pow(10u64.pow(9));
+        let fish = billion.pow(10u64.pow(9)).apply(&timers(TEST_INPUT));
+        let expected = fish.iter().fold(0, |acc, n| (acc + n) % m);
+        assert_eq!(count(TEST_INPUT, 10u64.pow(18), Some(m)), expected);
+    }
 }
@@ -1,32 +1,42 @@
-use std::{iter::repeat, str::FromStr};
+use std::str::FromStr;
+
+use aoc::convex::{around_mean, median, minimize};
 
 const INPUT: &str = include_str!("../../assets/p7.in");
 
-const fn sum(val: usize) -> usize {
+const fn sum(val: u128) -> u128 {
     val * (val + 1) / 2
 }
 
-fn solve(input: &str, sum: impl Fn((&usize, &usize)) -> usize) -> usize {
-    let pos: Vec<_> = input
+fn positions(input: &str) -> Vec<i64> {
+    input
         .split(',')
         .map(str::trim)
-        .map(usize::from_str)
+        .map(i64::from_str)
         .map(Result::unwrap)
-        .collect();
-    let min = *pos.iter().min().unwrap();
-    let max = *pos.iter().max().unwrap();
-    (min..=max)
-        .map(|ref x| pos.iter().zip(repeat(x)).map(&sum).sum())
-        .min()
-        .unwrap()
+        .collect()
+}
+
+/// Fuel needed to move all the crabs to `x` if every step costs one
+fn linear(pos: &[i64], x: i64) -> u128 {
+    pos.iter().map(|p| p.abs_diff(x) as u128).sum()
 }
 
-fn solve_1(input: &str) -> usize {
-    solve(input, |(a, b)| a.max(b) - a.min(b))
+/// Fuel needed to move all the crabs to `x` if every step costs one more
+/// than the previous one
+fn triangular(pos: &[i64], x: i64) -> u128 {
+    pos.iter().map(|p| sum(p.abs_diff(x) as u128)).sum()
 }
 
-fn solve_2(input: &str) -> usize {
-    solve(input, |(a, b)| sum(a.max(b) - a.min(b)))
+fn solve_1(input: &str) -> u128 {
+    let mut pos = positions(input);
+    let x = median(&mut pos);
+    linear(&pos, x)
+}
+
+fn solve_2(input: &str) -> u128 {
+    let pos = positions(input);
+    minimize(around_mean(&pos), |x| triangular(&pos, x)).1
 }
 
 fn main() {
@@ -36,6 +46,9 @@ fn main() {
 
 #[cfg(test)]
 mod tests {
+    use super::{linear, positions, triangular};
+    use aoc::convex::minimize;
+
     const TEST_INPUT: &str = "16,1,2,0,4,2,7,1,2,14";
 
     #[test]
@@ -43,4 +56,43 @@ mod tests {
         assert_eq!(super::solve_1(TEST_INPUT), 37);
         assert_eq!(super::solve_2(TEST_INPUT), 168);
     }
+
+    /// Tries every position between the leftmost and the rightmost crab
+    fn brute_force(pos: &[i64], cost: fn(&[i64], i64) -> u128) -> u128 {
+        let min = *pos.iter().min().unwrap();
+        let max = *pos.iter().max().unwrap();
+        (min..=max).map(|x| cost(pos, x)).min().unwrap()
+    }
+
+    #[test]
+    fn cross_check() {
+        let mut seed = 7u64;
+        let mut random = |n| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) as i64 % n
+        };
+        let mut inputs = vec![TEST_INPUT.to_string(), super::INPUT.to_string()];
+        for len in [1, 2, 3, 10, 51] {
+            let pos: Vec<_> = (0..len).map(|_| random(300) - 100).collect();
+            inputs.push(pos.iter().map(i64::to_string).collect::<Vec<_>>().join(","));
+        }
+        for input in &inputs {
+            let pos = positions(input);
+            assert_eq!(super::solve_1(input), brute_force(&pos, linear));
+            assert_eq!(super::solve_2(input), brute_force(&pos, triangular));
+        }
+
+        // a huge range, where the brute force would be too slow
+        let pos: Vec<_> = (0..100_000).map(|_| random(1 << 31) << 20).collect();
+        let input = pos.iter().map(i64::to_string).collect::<Vec<_>>().join(",");
+        let range = -(1 << 52)..=1 << 52;
+        assert_eq!(
+            super::solve_1(&input),
+            minimize(range.clone(), |x| linear(&pos, x)).1
+        );
+        assert_eq!(
+            super::solve_2(&input),
+            minimize(range, |x| triangular(&pos, x)).1
+        );
+    }
 }
@@ -1,11 +1,12 @@
-use std::{io::BufRead, mem::replace};
+use std::io::BufRead;
+
+use aoc::flood::{Components, Connectivity, Grid};
 
 const INPUT: &str = include_str!("../../assets/p9.in");
 
 struct Map<'a> {
     data: &'a [u8],
-    width: usize,
-    height: usize,
+    grid: Grid,
 }
 
 impl<'a> Map<'a> {
@@ -13,27 +14,38 @@ impl<'a> Map<'a> {
         let data = data.as_bytes();
         let width = data.iter().position(|v| v.is_ascii_whitespace()).unwrap();
         let height = data.lines().count();
-        Self {
-            data,
-            width,
-            height,
-        }
+        let grid = Grid::new(width, height, Connectivity::Four);
+        Self { data, grid }
     }
 
-    fn entry(&'a self, x: usize, y: usize) -> Option<Entry<'a>> {
-        Entry::new(self, x, y)
+    fn low_points(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.grid.cells().filter(|&cell| {
+            let value = self[cell];
+            self.grid.neighbours(cell).all(|n| self[n] > value)
+        })
     }
 
-    fn entries(&'a self) -> impl Iterator<Item = Entry<'a>> {
-        (0..self.width).flat_map(move |x| (0..self.height).map(move |y| self.entry(x, y).unwrap()))
+    /// Every cell other than the ones with height 9 is part of a basin
+    fn basins(&self) -> Components {
+        self.grid.components(|cell| self[cell] != b'9')
     }
 
-    fn low_point_entries(&'a self) -> impl Iterator<Item = Entry<'a>> {
-        self.entries().filter(|e| {
-            e.neighbours()
-                .into_iter()
-                .all(|n| none_or_ge_by_key(n, *e, Entry::value))
-        })
+    /// The map with each basin drawn in its own color
+    fn render(&self) -> String {
+        let labels = self.basins().labels;
+        let mut out = String::new();
+        for y in 0..self.grid.height {
+            for x in 0..self.grid.width {
+                let c = self[(x, y)] as char;
+                match labels[self.grid.index((x, y))] {
+                    // spread the colors of the 6x6x6 cube of the 256 colors
+                    Some(l) => out += &format!("\x1b[48;5;{}m{c}", 16 + l * 47 % 216),
+                    None => out += &format!("\x1b[0m{c}"),
+                }
+            }
+            out += "\x1b[0m\n";
+        }
+        out
     }
 }
 
@@ -41,100 +53,32 @@ impl std::ops::Index<(usize, usize)> for Map<'_> {
     type Output = u8;
 
     fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
-        self.data.get(x + y * (self.width + 1)).unwrap()
-    }
-}
-
-#[derive(Clone, Copy)]
-struct Entry<'a> {
-    map: &'a Map<'a>,
-    x: usize,
-    y: usize,
-}
-
-impl<'a> Entry<'a> {
-    fn new(map: &'a Map<'a>, x: usize, y: usize) -> Option<Self> {
-        Some(Self { map, x, y })
-            .filter(|_| x < map.width)
-            .filter(|_| y < map.height)
-    }
-
-    fn value(self) -> u8 {
-        self.map[(self.x, self.y)]
-    }
-
-    fn left(self) -> Option<Self> {
-        (self.x > 0).then(|| Self::new(self.map, self.x - 1, self.y).unwrap())
-    }
-
-    fn right(self) -> Option<Self> {
-        Self::new(self.map, self.x + 1, self.y)
-    }
-
-    fn up(self) -> Option<Self> {
-        (self.y > 0).then(|| Self::new(self.map, self.x, self.y - 1).unwrap())
-    }
-
-    fn down(self) -> Option<Self> {
-        Self::new(self.map, self.x, self.y + 1)
-    }
-
-    fn cost(self) -> usize {
-        (self.value() - b'0') as usize + 1
-    }
-
-    fn neighbours(self) -> [Option<Self>; 4] {
-        [self.left(), self.right(), self.up(), self.down()]
-    }
-}
-
-/// None or grater than by key
-fn none_or_ge_by_key<T, F, O>(a: Option<T>, b: T, f: F) -> bool
-where
-    F: Fn(T) -> O,
-    O: PartialOrd,
-{
-    match a {
-        None => true,
-        Some(v) => f(v) > f(b),
+        self.data.get(x + y * (self.grid.width + 1)).unwrap()
     }
 }
 
 fn solve_1(input: &str) -> usize {
-    Map::new(input).low_point_entries().map(Entry::cost).sum()
+    let map = Map::new(input);
+    let risk = |cell| (map[cell] - b'0') as usize + 1;
+    map.low_points().map(risk).sum()
 }
 
 fn solve_2(input: &str) -> usize {
-    let map = Map::new(input);
-    let mut stack = Vec::with_capacity(map.width * map.height);
-    let mut seen = vec![false; map.width * map.height];
-    map.low_point_entries()
-        .map(|entry| {
-            let mut count = 0;
-            stack.clear();
-            stack.push(entry);
-            seen.fill(false);
-            while let Some(entry) = stack.pop() {
-                if !replace(&mut seen[entry.x + entry.y * map.width], true) && entry.value() != b'9'
-                {
-                    count += 1;
-                    stack.extend(entry.neighbours().into_iter().flatten());
-                }
-            }
-            count
-        })
-        .fold([0, 0, 0], |[a, b, c], basin| {
-            [
-                basin.max(a),
-                basin.min(a).max(b),
-                basin.min(a).min(b).max(c),
-            ]
-        })
+    let mut sizes: Vec<_> = Map::new(input)
+        .basins()
+        .components
         .into_iter()
-        .product()
+        .map(|c| c.size)
+        .collect();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes.iter().take(3).product()
 }
 
 fn main() {
+    if std::env::args().nth(1).as_deref() == Some("--basins") {
+        print!("{}", Map::new(INPUT).render());
+        return;
+    }
     println!("Answer 1: {}", solve_1(INPUT));
     println!("Answer 2: {}", solve_2(INPUT));
 }
@@ -154,4 +98,19 @@ mod tests {
         assert_eq!(super::solve_1(TEST_INPUT), 15);
         assert_eq!(super::solve_2(TEST_INPUT), 1134);
     }
+
+    #[test]
+    fn basins() {
+        let map = super::Map::new(TEST_INPUT);
+        let basins = map.basins().components;
+        let sizes: Vec<_> = basins.iter().map(|b| b.size).collect();
+        assert_eq!(sizes, [3, 9, 14, 9]);
+        assert_eq!((basins[2].min, basins[2].max), ((0, 1), (5, 4)));
+        // one basin for each low point
+        assert_eq!(map.low_points().count(), basins.len());
+
+        let render = map.render();
+        assert_eq!(render.lines().count(), 5);
+        assert!(render.starts_with("\x1b[48;5;16m2\x1b[48;5;16m1\x1b[0m9"));
+    }
 }
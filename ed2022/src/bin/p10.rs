@@ -1,4 +1,4 @@
-use std::{iter::from_fn, str::FromStr};
+use std::str::FromStr;
 
 const INPUT: &str = include_str!("../../assets/p10.in");
 
@@ -21,58 +21,136 @@ impl FromStr for Instruction {
     }
 }
 
-fn instructions(input: &str) -> impl Iterator<Item = Instruction> + '_ {
-    use Instruction::*;
-    let mut iter = input.lines().map(str::parse).map(Result::unwrap);
-    let mut temp = None;
-    from_fn(move || {
-        temp.take().or_else(|| {
-            if let i @ Addx(_) = iter.next()? {
-                temp = Some(i)
+impl Instruction {
+    /// Number of cycles the instruction takes to complete
+    fn cycles(&self) -> usize {
+        match self {
+            Self::Noop => 1,
+            Self::Addx(_) => 2,
+        }
+    }
+
+    fn execute(&self, regs: &mut Registers) {
+        match *self {
+            Self::Noop => (),
+            Self::Addx(n) => regs.x += n,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Registers {
+    x: isize,
+}
+
+/// Something that looks at the registers during every cycle
+trait Observer {
+    fn tick(&mut self, cycle: usize, regs: &Registers);
+}
+
+impl<F: FnMut(usize, &Registers)> Observer for F {
+    fn tick(&mut self, cycle: usize, regs: &Registers) {
+        self(cycle, regs)
+    }
+}
+
+struct Cpu<'a> {
+    regs: Registers,
+    /// Number of the current cycle, starting from 1
+    cycle: usize,
+    observers: Vec<&'a mut dyn Observer>,
+}
+
+impl<'a> Cpu<'a> {
+    fn new() -> Self {
+        Self {
+            regs: Registers { x: 1 },
+            cycle: 1,
+            observers: vec![],
+        }
+    }
+
+    fn attach(&mut self, observer: &'a mut dyn Observer) -> &mut Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Executes the instruction, the registers are updated only after the
+    /// last cycle it takes
+    fn execute(&mut self, i: Instruction) {
+        for _ in 0..i.cycles() {
+            for observer in &mut self.observers {
+                observer.tick(self.cycle, &self.regs);
             }
-            Some(Noop)
-        })
-    })
-}
-
-fn solve_1(input: &str) -> isize {
-    let mut x = 1;
-    let mut strength = 0;
-    for (cycle, i) in instructions(input).enumerate() {
-        let cycle: isize = cycle.try_into().unwrap();
-        if cycle > 18 && (cycle - 19) % 40 == 0 {
-            let add = x * (cycle + 1);
-            strength += add;
+            self.cycle += 1;
         }
-        if let Instruction::Addx(add) = i {
-            x += add;
+        i.execute(&mut self.regs);
+    }
+
+    fn run(&mut self, program: impl IntoIterator<Item = Instruction>) {
+        program.into_iter().for_each(|i| self.execute(i))
+    }
+}
+
+/// Sum of the signal strengths during the 20th cycle and every 40 after that
+#[derive(Default)]
+struct SignalStrength(isize);
+
+impl Observer for SignalStrength {
+    fn tick(&mut self, cycle: usize, regs: &Registers) {
+        if cycle % 40 == 20 {
+            self.0 += regs.x * isize::try_from(cycle).unwrap();
         }
     }
-    strength
 }
 
-fn solve_2(input: &str) -> String {
-    let mut x = 1;
-    let mut display = [[b'.'; 40]; 6];
-    for (cycle, i) in instructions(input).enumerate() {
-        let pixel = (cycle % 40).try_into().unwrap();
-        if x + 1 >= pixel && x - 1 <= pixel {
-            display[cycle / 40][cycle % 40] = b'#';
+/// Screen that draws a pixel each cycle, lit if the sprite is over it
+struct Crt([[u8; 40]; 6]);
+
+impl Crt {
+    fn new() -> Self {
+        Self([[b'.'; 40]; 6])
+    }
+}
+
+impl Observer for Crt {
+    fn tick(&mut self, cycle: usize, regs: &Registers) {
+        let [row, col] = [(cycle - 1) / 40 % 6, (cycle - 1) % 40];
+        if (regs.x - 1..=regs.x + 1).contains(&col.try_into().unwrap()) {
+            self.0[row][col] = b'#';
         }
-        if let Instruction::Addx(add) = i {
-            x += add;
+    }
+}
+
+impl std::fmt::Display for Crt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.0 {
+            writeln!(f, "{}", std::str::from_utf8(&row).unwrap())?;
         }
+        Ok(())
     }
-    let chars = display
-        .into_iter()
-        .flat_map(|row| row.into_iter().chain([b'\n']))
-        .collect();
-    String::from_utf8(chars).unwrap()
+}
+
+fn instructions(input: &str) -> impl Iterator<Item = Instruction> + '_ {
+    input.lines().map(str::parse).map(Result::unwrap)
+}
+
+/// Runs the program once with both observers attached, returning the signal
+/// strength and the screen
+fn solve(input: &str) -> (isize, Crt) {
+    let mut strength = SignalStrength::default();
+    let mut crt = Crt::new();
+    Cpu::new()
+        .attach(&mut strength)
+        .attach(&mut crt)
+        .run(instructions(input));
+    (strength.0, crt)
 }
 
 fn main() {
-    println!("Answer 1: {}", solve_1(INPUT));
-    println!("Answer 2: \n{}", solve_2(INPUT));
+    let (strength, crt) = solve(INPUT);
+    println!("Answer 1: {strength}");
+    println!("Answer 2: \n{crt}");
 }
 
 #[cfg(test)]
@@ -234,7 +312,25 @@ mod tests {
 
     #[test]
     fn test() {
-        assert_eq!(super::solve_1(TEST_INPUT), 13140);
-        assert_eq!(super::solve_2(TEST_INPUT), OUTPUT);
+        let (strength, crt) = super::solve(TEST_INPUT);
+        assert_eq!(strength, 13140);
+        assert_eq!(crt.to_string(), OUTPUT);
+    }
+
+    #[test]
+    fn observers() {
+        use super::{instructions, Cpu, Registers};
+
+        // every observer sees every cycle of the same run
+        let mut seen = vec![];
+        let mut record = |cycle, regs: &Registers| seen.push((cycle, regs.x));
+        let mut cycles = 0;
+        let mut count = |_, _: &Registers| cycles += 1;
+        Cpu::new()
+            .attach(&mut record)
+            .attach(&mut count)
+            .run(instructions("noop\naddx 3\naddx -5\nnoop"));
+        assert_eq!(seen, [(1, 1), (2, 1), (3, 1), (4, 4), (5, 4), (6, -1)]);
+        assert_eq!(cycles, 6);
     }
 }
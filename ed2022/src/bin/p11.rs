@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     convert::identity,
     ops::{Add, Mul, Not},
     str::FromStr,
@@ -159,7 +160,83 @@ fn monkey_business<const ROUNDS: usize, const RELIEF: usize>(input: &str) -> usi
     a.inspections * b.inspections
 }
 
+/// Inspections made by each monkey on a single item in the given number of
+/// rounds, without relief
+///
+/// The item is simulated alone, as it doesn't interact with the others, until
+/// its state at the start of a round (monkey holding it and worry level modulo
+/// `div`) repeats. The counts are then extrapolated from the cycle.
+fn item_inspections(
+    monkeys: &[Monke],
+    monkey: usize,
+    worry: usize,
+    div: usize,
+    rounds: u64,
+) -> Vec<u64> {
+    let mut seen = HashMap::new();
+    // inspections after each number of rounds
+    let mut history = vec![vec![0; monkeys.len()]];
+    let (mut at, mut worry) = (monkey, worry % div);
+    for round in 0..rounds as usize {
+        if let Some(start) = seen.insert((at, worry), round) {
+            let len = (round - start) as u64;
+            let (cycles, rest) = ((rounds - start as u64) / len, (rounds - start as u64) % len);
+            let [first, last] = [start, round].map(|r| &history[r]);
+            let partial = &history[start + rest as usize];
+            return (0..monkeys.len())
+                .map(|m| partial[m] + cycles * (last[m] - first[m]))
+                .collect();
+        }
+        let mut inspections = history.last().unwrap().clone();
+        loop {
+            let monke = &monkeys[at];
+            inspections[at] += 1;
+            worry = monke.op.exec(worry) % div;
+            let to = if worry % monke.test == 0 {
+                monke.throw.0
+            } else {
+                monke.throw.1
+            };
+            // monkeys that already had their turn get the item the next round
+            let next_round = to <= at;
+            at = to;
+            if next_round {
+                break;
+            }
+        }
+        history.push(inspections);
+    }
+    history.pop().unwrap()
+}
+
+/// Monkey business without relief after any number of rounds, computed by
+/// analysing each item on its own
+fn periodic_monkey_business(input: &str, rounds: u64) -> u128 {
+    let mut monkeys: Vec<_> = monkeys(input).collect();
+    monkeys.sort_unstable_by_key(|m| m.num);
+    let div: usize = monkeys.iter().map(|m| m.test).product();
+    let mut inspections = vec![0; monkeys.len()];
+    for (monkey, items) in monkeys.iter().map(|m| &m.items).enumerate() {
+        for &item in items {
+            let counts = item_inspections(&monkeys, monkey, item, div, rounds);
+            inspections
+                .iter_mut()
+                .zip(counts)
+                .for_each(|(i, c)| *i += c);
+        }
+    }
+    inspections.sort_unstable();
+    let [.., a, b] = inspections[..] else {
+        panic!()
+    };
+    a as u128 * b as u128
+}
+
 fn main() {
+    if let Some(rounds) = std::env::args().nth(1) {
+        let rounds = rounds.parse().unwrap();
+        return println!("{}", periodic_monkey_business(INPUT, rounds));
+    }
     println!("Answer 1: {}", monkey_business::<20, 3>(INPUT));
     println!("Answer 2: {}", monkey_business::<10000, 1>(INPUT));
 }
@@ -201,4 +278,20 @@ mod tests {
         assert_eq!(super::monkey_business::<20, 3>(TEST_INPUT), 10605);
         assert_eq!(super::monkey_business::<10000, 1>(TEST_INPUT), 2713310158);
     }
+
+    #[test]
+    fn periodic() {
+        use super::{monkey_business, periodic_monkey_business, INPUT};
+
+        for input in [TEST_INPUT, INPUT] {
+            let direct = [
+                monkey_business::<1, 1>(input),
+                monkey_business::<20, 1>(input),
+                monkey_business::<1000, 1>(input),
+                monkey_business::<10000, 1>(input),
+            ];
+            let periodic = [1, 20, 1000, 10000].map(|r| periodic_monkey_business(input, r));
+            assert_eq!(direct.map(|d| d as u128), periodic);
+        }
+    }
 }
@@ -1,95 +1,102 @@
-use std::{array, cmp::Ordering, iter};
+use std::{array, cmp::Ordering, fmt, slice, str::FromStr};
+
+use aoc::json::{self, Value};
 
 const INPUT: &str = include_str!("../../assets/p13.in");
 
-#[derive(Clone, Copy, Debug)]
-enum Token {
-    Open,
+#[derive(Clone, Debug)]
+enum Item {
     Num(usize),
-    Close,
+    List(Vec<Self>),
 }
 
-fn tokens(mut packet: &str) -> impl Iterator<Item = Token> + '_ {
-    iter::from_fn(move || {
-        while let Some(rest) = packet.strip_prefix(',') {
-            packet = rest;
-        }
-        if let Some(rest) = packet.strip_prefix('[') {
-            packet = rest;
-            Some(Token::Open)
-        } else if let Some(rest) = packet.strip_prefix(']') {
-            packet = rest;
-            Some(Token::Close)
-        } else {
-            let digits = packet.find([',', ']'])?;
-            let (digits, rest) = packet.split_at(digits);
-            packet = rest;
-            digits.parse().ok().map(Token::Num)
+/// Numbers compared with lists are treated as lists with only that number
+impl Ord for Item {
+    fn cmp(&self, other: &Self) -> Ordering {
+        use Item::*;
+        match (self, other) {
+            (Num(l), Num(r)) => l.cmp(r),
+            (List(l), List(r)) => l.cmp(r),
+            (List(l), Num(_)) => l.as_slice().cmp(slice::from_ref(other)),
+            (Num(_), List(r)) => slice::from_ref(self).cmp(r),
         }
-    })
+    }
 }
 
-#[derive(Debug)]
-enum Item {
-    Num(usize),
-    List(Vec<Self>),
+impl PartialOrd for Item {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-impl Item {
-    fn new(mut tks: impl Iterator<Item = Token>) -> Self {
-        match tks.next() {
-            Some(Token::Open) => Self::list(&mut tks),
-            Some(Token::Num(n)) => Item::Num(n),
-            _ => panic!(),
-        }
+impl PartialEq for Item {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
     }
+}
 
-    fn list(tks: &mut impl Iterator<Item = Token>) -> Self {
-        let mut list = vec![];
-        while let Some(token) = tks.next() {
-            match token {
-                Token::Open => list.push(Self::list(tks)),
-                Token::Num(n) => list.push(Item::Num(n)),
-                Token::Close => break,
+impl Eq for Item {}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Item::Num(n) => write!(f, "{n}"),
+            Item::List(list) => {
+                write!(f, "[")?;
+                for (i, item) in list.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
             }
         }
-        Self::List(list)
     }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ParseError {
+    Json(json::Error),
+    /// Value that is neither a list nor a number that fits in a `usize`
+    NotAnItem(Value),
+}
 
-    fn listed(&mut self) -> &mut Self {
-        match *self {
-            Self::Num(n) => *self = Self::List(vec![Self::Num(n)]),
-            Self::List(_) => (),
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Json(err) => write!(f, "{err}"),
+            ParseError::NotAnItem(value) => write!(f, "`{value}` is not a packet item"),
         }
-        self
     }
+}
 
-    fn cmp(&mut self, rhs: &mut Self) -> Ordering {
-        use Item::*;
-        match (self, rhs) {
-            (Num(l), Num(r)) => l.cmp(&r),
-            (l @ List(_), r @ Num(_)) => l.cmp(r.listed()),
-            (l @ Num(_), r @ List(_)) => l.listed().cmp(r),
-            (List(l), List(r)) => Self::cmp_lists(l, r),
+impl TryFrom<Value> for Item {
+    type Error = ParseError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Number(n) => match usize::try_from(n) {
+                Ok(n) => Ok(Item::Num(n)),
+                Err(_) => Err(ParseError::NotAnItem(value)),
+            },
+            Value::Array(list) => list
+                .into_iter()
+                .map(Item::try_from)
+                .collect::<Result<_, _>>()
+                .map(Item::List),
+            value => Err(ParseError::NotAnItem(value)),
         }
     }
+}
 
-    fn cmp_lists(lhs: &mut [Self], rhs: &mut [Self]) -> Ordering {
-        use Ordering::*;
-        let [mut l, mut r] = [lhs.iter_mut(), rhs.iter_mut()];
-        for items in iter::repeat_with(|| [l.next(), r.next()]) {
-            return match items {
-                [Some(_), None] => Greater,
-                [None, Some(_)] => Less,
-                [None, None] => Equal,
-                [Some(l), Some(r)] => match l.cmp(r) {
-                    Less => Less,
-                    Greater => Greater,
-                    Equal => continue,
-                },
-            };
-        }
-        unreachable!()
+/// Packets are parsed as JSON, which they are a subset of
+impl FromStr for Item {
+    type Err = ParseError;
+
+    fn from_str(packet: &str) -> Result<Self, Self::Err> {
+        let value: Value = packet.parse().map_err(ParseError::Json)?;
+        value.try_into()
     }
 }
 
@@ -101,8 +108,8 @@ fn item_pairs(input: &str) -> impl Iterator<Item = [Item; 2]> + '_ {
     input.split(eol).map(str::lines).map(|mut lines| {
         array::from_fn(|_| lines.next())
             .map(Option::unwrap)
-            .map(tokens)
-            .map(Item::new)
+            .map(str::parse)
+            .map(Result::unwrap)
     })
 }
 
@@ -110,39 +117,26 @@ fn items(input: &str) -> impl Iterator<Item = Item> + '_ {
     input
         .lines()
         .filter(|s| !s.is_empty())
-        .map(tokens)
-        .map(Item::new)
-}
-
-fn binary_search_item(items: &mut [Item], new: &mut Item) -> usize {
-    let [_, ..] = items else { return 0 };
-    let mid = items.len() / 2;
-    match items[mid].cmp(new) {
-        Ordering::Greater => binary_search_item(&mut items[..mid], new),
-        Ordering::Equal => mid,
-        Ordering::Less => binary_search_item(&mut items[mid + 1..], new) + mid + 1,
-    }
+        .map(str::parse)
+        .map(Result::unwrap)
 }
 
 fn solve_1(input: &str) -> usize {
     item_pairs(input)
-        .map(|[mut l, mut r]| l.cmp(&mut r))
+        .map(|[l, r]| l.cmp(&r))
         .enumerate()
         .filter_map(|(i, b)| b.is_le().then_some(i + 1))
         .sum()
 }
 
 fn solve_2(input: &str) -> usize {
-    use Item::*;
-
-    let items = &mut vec![];
-    for mut item in self::items(input) {
-        let i = binary_search_item(items, &mut item);
-        items.insert(i, item);
-    }
-    let div2 = 1 + binary_search_item(items, &mut List(vec![List(vec![Num(2)])]));
-    let div6 = 2 + binary_search_item(items, &mut List(vec![List(vec![Num(6)])]));
-    div2 * div6
+    let dividers: [Item; 2] = ["[[2]]", "[[6]]"].map(|d| d.parse().unwrap());
+    let mut items: Vec<_> = items(input).chain(dividers.clone()).collect();
+    items.sort();
+    dividers
+        .iter()
+        .map(|d| items.binary_search(d).unwrap() + 1)
+        .product()
 }
 
 fn main() {
@@ -182,4 +176,38 @@ mod tests {
         assert_eq!(super::solve_1(TEST_INPUT), 13);
         assert_eq!(super::solve_2(TEST_INPUT), 140);
     }
+
+    #[test]
+    fn display() {
+        use super::{Item, INPUT};
+
+        let packets = TEST_INPUT.lines().chain(INPUT.lines());
+        for packet in packets.filter(|line| !line.trim().is_empty()) {
+            let item: Item = packet.parse().unwrap();
+            assert_eq!(item.to_string(), packet);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        use super::{Item, ParseError};
+        use aoc::json::{Error, ErrorKind, Value};
+
+        let err = |column, kind| {
+            Err(ParseError::Json(Error {
+                line: 1,
+                column,
+                kind,
+            }))
+        };
+        assert_eq!("[1,[2,3]".parse::<Item>(), err(9, ErrorKind::UnexpectedEnd));
+        assert_eq!("[1,,2]".parse::<Item>(), err(4, ErrorKind::Unexpected(',')));
+        assert_eq!("[1]]".parse::<Item>(), err(4, ErrorKind::Unexpected(']')));
+        let not_an_item = ParseError::NotAnItem(Value::String("a".into()));
+        assert_eq!("[1,[\"a\"]]".parse::<Item>(), Err(not_an_item));
+        assert_eq!(
+            "[-1]".parse::<Item>().unwrap_err().to_string(),
+            "`-1` is not a packet item"
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use std::iter::{from_fn, repeat};
+use std::{fmt, iter::from_fn, str::FromStr};
 
 const INPUT: &str = include_str!("../../assets/p5.in");
 
@@ -22,64 +22,248 @@ fn crates(line: &str) -> impl Iterator<Item = Option<u8>> + '_ {
     })
 }
 
-fn stacks(stacks: &str) -> Vec<Stack> {
-    stacks
-        .lines()
-        .flat_map(|line| crates(line).enumerate())
-        .flat_map(|(i, opt)| opt.map(|v| (i, v)))
-        .fold(vec![], |mut vec, (i, v)| {
-            if let Some(extend) = i.checked_sub(vec.len()) {
-                vec.extend(repeat(vec![]).take(extend + 1));
+/// Stacks of crates, from the bottom to the top
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Stacks(Vec<Stack>);
+
+/// Whether the row only has crates like `[A]` or empty slots, separated by
+/// spaces
+fn well_formed(row: &str) -> bool {
+    row.as_bytes().chunks(4).all(|slot| match slot {
+        [b'[', c, b']', rest @ ..] => *c != b' ' && matches!(rest, [] | [b' ']),
+        blank => blank.iter().all(|&b| b == b' '),
+    })
+}
+
+/// Parses the rows of crates followed by the line numbering the stacks, which
+/// tells how many stacks there are, even if some are empty
+impl FromStr for Stacks {
+    type Err = Error;
+
+    fn from_str(drawing: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<_> = drawing.lines().collect();
+        let malformed = |idx: usize| Error::Drawing { line: idx + 1 };
+        let Some((numbers, rows)) = lines.split_last() else {
+            return Err(malformed(0));
+        };
+        let numbers = numbers.split_whitespace().zip(1..);
+        if !numbers.clone().all(|(n, i)| n.parse() == Ok(i)) {
+            return Err(malformed(rows.len()));
+        }
+        let mut stacks = vec![Stack::new(); numbers.count()];
+        // from the bottom, so that the crates are pushed in order
+        for (idx, row) in rows.iter().enumerate().rev() {
+            if !well_formed(row) {
+                return Err(malformed(idx));
             }
-            vec[i].insert(0, v);
-            vec
-        })
+            let below = rows.len() - 1 - idx;
+            for (i, item) in crates(row).enumerate() {
+                let Some(item) = item else { continue };
+                // crates must be inside a stack and can't float over a gap
+                match stacks.get_mut(i) {
+                    Some(stack) if stack.len() == below => stack.push(item),
+                    _ => return Err(malformed(idx)),
+                }
+            }
+        }
+        Ok(Self(stacks))
+    }
+}
+
+/// Draws the stacks the same way they are drawn in the puzzle input
+impl fmt::Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let height = self.0.iter().map(Vec::len).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let row = self.0.iter().map(|stack| match stack.get(level) {
+                Some(&c) => format!("[{}]", c as char),
+                None => "   ".to_string(),
+            });
+            writeln!(f, "{}", row.collect::<Vec<_>>().join(" "))?;
+        }
+        let numbers = (1..=self.0.len()).map(|n| format!(" {n} "));
+        write!(f, "{}", numbers.collect::<Vec<_>>().join(" "))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Crane {
+    /// Moves one crate at a time
+    CrateMover9000,
+    /// Moves all the crates at once, retaining their order
+    CrateMover9001,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Move {
+    count: usize,
+    /// Index of the stack where the crates are taken
+    from: usize,
+    /// Index of the stack where the crates are put
+    to: usize,
+    /// Line of the input where the move is written
+    line: usize,
 }
 
-type Instruction = (usize, usize, usize);
+#[derive(Debug, PartialEq, Eq)]
+enum Error {
+    Drawing {
+        line: usize,
+    },
+    Malformed {
+        line: usize,
+    },
+    NoSuchStack {
+        line: usize,
+        stack: usize,
+    },
+    NotEnough {
+        line: usize,
+        count: usize,
+        from: usize,
+        present: usize,
+    },
+}
 
-fn instruction(rule: &str) -> Option<Instruction> {
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Error::Drawing { line } => write!(f, "line {line}: malformed drawing"),
+            Error::Malformed { line } => write!(f, "line {line}: malformed move"),
+            Error::NoSuchStack { line, stack } => {
+                write!(f, "line {line}: there is no stack {}", stack + 1)
+            }
+            Error::NotEnough {
+                line,
+                count,
+                from,
+                present,
+            } => write!(
+                f,
+                "line {line}: move {count} crates from stack {} which has {present}",
+                from + 1
+            ),
+        }
+    }
+}
+
+impl Stacks {
+    fn apply(&mut self, mov: Move, crane: Crane) -> Result<(), Error> {
+        let Move {
+            count,
+            from,
+            to,
+            line,
+        } = mov;
+        for stack in [from, to] {
+            if stack >= self.0.len() {
+                return Err(Error::NoSuchStack { line, stack });
+            }
+        }
+        let present = self.0[from].len();
+        let Some(retain) = present.checked_sub(count) else {
+            return Err(Error::NotEnough {
+                line,
+                count,
+                from,
+                present,
+            });
+        };
+        let mut moved = self.0[from].split_off(retain);
+        if crane == Crane::CrateMover9000 {
+            moved.reverse();
+        }
+        self.0[to].append(&mut moved);
+        Ok(())
+    }
+
+    fn tops(&self) -> String {
+        let tops = self.0.iter().flat_map(|stack| stack.last());
+        String::from_utf8(tops.copied().collect()).unwrap()
+    }
+}
+
+fn instruction(rule: &str) -> Option<(usize, usize, usize)> {
     let (mov, rule) = rule.strip_prefix("move ")?.split_once(' ')?;
     let (from, rule) = rule.strip_prefix("from ")?.split_once(' ')?;
     let to = rule.strip_prefix("to ")?;
     let parse = |s: &str| s.parse::<usize>().ok();
-    Some((parse(mov)?, parse(from)? - 1, parse(to)? - 1))
+    Some((
+        parse(mov)?,
+        parse(from)?.checked_sub(1)?,
+        parse(to)?.checked_sub(1)?,
+    ))
 }
 
-fn instructions(rules: &str) -> impl Iterator<Item = Instruction> + '_ {
-    rules.lines().map(instruction).map(Option::unwrap)
+/// Moves in the rearrangement procedure, `first_line` is the line number of
+/// the first one
+fn instructions(rules: &str, first_line: usize) -> impl Iterator<Item = Result<Move, Error>> + '_ {
+    rules.lines().zip(first_line..).map(|(rule, line)| {
+        let (count, from, to) = instruction(rule).ok_or(Error::Malformed { line })?;
+        Ok(Move {
+            count,
+            from,
+            to,
+            line,
+        })
+    })
 }
 
-fn execute<const RETAIN_ORDER: bool>(input: &str) -> impl Iterator<Item = u8> {
-    let (stacks, instructions) = split(input);
-    let mut stacks = self::stacks(stacks);
-
-    for (mov, from, to) in self::instructions(instructions) {
-        let [a, .., b] = &mut stacks[from.min(to)..=from.max(to)] else { continue };
-        let (from, to) = if from < to { (a, b) } else { (b, a) };
-
-        let retain = from.len() - mov;
-        if RETAIN_ORDER {
-            to.extend_from_slice(&from[retain..])
-        } else {
-            to.extend(from[retain..].iter().rev())
-        }
-        from.truncate(retain);
+/// Executes the procedure, calling `on_move` after every move
+fn execute(
+    input: &str,
+    crane: Crane,
+    mut on_move: impl FnMut(Move, &Stacks),
+) -> Result<Stacks, Error> {
+    let (drawing, rules) = split(input);
+    let mut stacks: Stacks = drawing.parse()?;
+    for mov in instructions(rules, drawing.lines().count() + 2) {
+        let mov = mov?;
+        stacks.apply(mov, crane)?;
+        on_move(mov, &stacks);
     }
-    stacks.into_iter().map(|v| *v.last().unwrap())
+    Ok(stacks)
 }
 
 fn solve_1(input: &str) -> String {
-    String::from_utf8(execute::<false>(input).collect()).unwrap()
+    execute(input, Crane::CrateMover9000, |_, _| ())
+        .unwrap()
+        .tops()
 }
 
 fn solve_2(input: &str) -> String {
-    String::from_utf8(execute::<true>(input).collect()).unwrap()
+    execute(input, Crane::CrateMover9001, |_, _| ())
+        .unwrap()
+        .tops()
 }
 
 fn main() {
-    println!("Answer 1: {}", solve_1(INPUT));
-    println!("Answer 2: {}", solve_2(INPUT));
+    let crane = match std::env::args().nth(1).as_deref() {
+        Some("--show-9000") => Crane::CrateMover9000,
+        Some("--show-9001") => Crane::CrateMover9001,
+        _ => {
+            println!("Answer 1: {}", solve_1(INPUT));
+            println!("Answer 2: {}", solve_2(INPUT));
+            return;
+        }
+    };
+    let result = execute(
+        INPUT,
+        crane,
+        |Move {
+             count,
+             from,
+             to,
+             line,
+         },
+         stacks| {
+            let (from, to) = (from + 1, to + 1);
+            println!("{line}: move {count} from {from} to {to}\n{stacks}\n");
+        },
+    );
+    if let Err(err) = result {
+        eprintln!("{err}");
+    }
 }
 
 #[cfg(test)]
@@ -99,4 +283,76 @@ mod tests {
         assert_eq!(super::solve_1(TEST_INPUT), "CMZ");
         assert_eq!(super::solve_2(TEST_INPUT), "MCD");
     }
+
+    #[test]
+    fn drawing() {
+        use super::{split, Stacks, INPUT};
+
+        let drawing = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+        for drawing in [drawing, split(INPUT).0] {
+            let stacks: Stacks = drawing.parse().unwrap();
+            assert_eq!(stacks.to_string(), drawing);
+        }
+
+        // the last stack is empty, only the numbers tell that it's there
+        let stacks = Stacks(vec![vec![b'A'], vec![]]);
+        let drawing = stacks.to_string();
+        assert_eq!(drawing, "[A]    \n 1   2 ");
+        assert_eq!(drawing.parse(), Ok(stacks));
+        let empty = Stacks(vec![vec![]; 3]);
+        assert_eq!(empty.to_string().parse(), Ok(empty));
+    }
+
+    #[test]
+    fn malformed_drawings() {
+        use super::{Error, Stacks};
+
+        let drawings = [
+            ("", 1),
+            ("[A] [B]\n 1   3 ", 2),
+            ("[A] (B)\n 1   2 ", 1),
+            ("    [B] [C]\n 1   2 ", 1),
+            ("[A]\n    \n 1 ", 1),
+        ];
+        for (drawing, line) in drawings {
+            assert_eq!(drawing.parse::<Stacks>(), Err(Error::Drawing { line }));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        use super::{execute, Crane, Error};
+
+        let input = TEST_INPUT.replace("move 2 from 2 to 1", "move 3 from 2 to 1");
+        let err = execute(&input, Crane::CrateMover9001, |_, _| ()).unwrap_err();
+        let expected = Error::NotEnough {
+            line: 8,
+            count: 3,
+            from: 1,
+            present: 2,
+        };
+        assert_eq!(err, expected);
+        assert_eq!(
+            err.to_string(),
+            "line 8: move 3 crates from stack 2 which has 2"
+        );
+
+        let input = TEST_INPUT.replace("to 2", "to 4");
+        let err = execute(&input, Crane::CrateMover9000, |_, _| ()).unwrap_err();
+        assert_eq!(err, Error::NoSuchStack { line: 9, stack: 3 });
+    }
+
+    #[test]
+    fn frames() {
+        use super::{execute, Crane};
+
+        let mut frames = vec![];
+        let stacks = execute(TEST_INPUT, Crane::CrateMover9000, |_, stacks| {
+            frames.push(stacks.to_string())
+        });
+        let first = "[D]        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0], first);
+        assert_eq!(frames[3], stacks.unwrap().to_string());
+    }
 }
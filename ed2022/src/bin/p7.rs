@@ -1,95 +1,332 @@
-use std::iter::once;
+use aoc::dot::Dot;
+use std::{collections::BTreeMap, fmt};
 
 const INPUT: &str = include_str!("../../assets/p7.in");
 
-enum Entry {
-    Exit,
-    Enter,
-    Root,
+enum Entry<'a> {
+    Cd(&'a str),
     List,
-    File(usize),
-    Dir,
+    File(usize, &'a str),
+    Dir(&'a str),
 }
 
-impl Entry {
-    fn parse(line: &str) -> Option<Self> {
-        match line.strip_prefix("$ cd ") {
-            Some("/") => return Some(Self::Root),
-            Some("..") => return Some(Self::Exit),
-            Some(_) => return Some(Self::Enter),
-            None => (),
-        };
+impl<'a> Entry<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        if let Some(path) = line.strip_prefix("$ cd ") {
+            return Some(Self::Cd(path));
+        }
         Some(match line.split_once(' ')? {
             ("$", "ls") => Self::List,
-            ("dir", _) => Self::Dir,
-            (size, _) => Self::File(size.parse().ok()?),
+            ("$", _) => None?,
+            ("dir", name) => Self::Dir(name),
+            (size, name) => Self::File(size.parse().ok()?, name),
         })
     }
 }
 
-struct Fs<F> {
-    stack: Vec<usize>,
-    used: usize,
-    on_exit: F,
+/// Something unexpected found in the terminal output
+#[derive(Debug, PartialEq, Eq)]
+enum Anomaly {
+    Unknown(String),
+    AboveRoot,
+    NotADirectory(String),
+    IsADirectory(String),
+    SizeConflict {
+        path: String,
+        old: usize,
+        new: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Warning {
+    line: usize,
+    anomaly: Anomaly,
 }
 
-impl<F: FnMut(usize)> Fs<F> {
-    fn new(on_exit: F) -> Self {
-        Self {
-            stack: vec![],
-            used: 0,
-            on_exit,
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.anomaly {
+            Anomaly::Unknown(line) => write!(f, "unrecognized line `{line}`"),
+            Anomaly::AboveRoot => write!(f, "`cd ..` above the root"),
+            Anomaly::NotADirectory(path) => write!(f, "{path} is a file, not a directory"),
+            Anomaly::IsADirectory(path) => write!(f, "{path} is a directory, not a file"),
+            Anomaly::SizeConflict { path, old, new } => {
+                write!(f, "{path} has size {new} but was listed with {old}")
+            }
         }
     }
+}
+
+const ROOT: usize = 0;
+
+enum Kind<'a> {
+    File(usize),
+    Dir(BTreeMap<&'a str, usize>),
+}
+
+struct Node<'a> {
+    name: &'a str,
+    parent: usize,
+    kind: Kind<'a>,
+}
+
+/// Tree of the files and directories seen in the terminal output, where each
+/// node is referred to by its index and the root is [`ROOT`]
+///
+/// Nodes are always added after their parent, so their index is greater.
+struct Fs<'a> {
+    nodes: Vec<Node<'a>>,
+}
+
+impl<'a> Fs<'a> {
+    fn new() -> Self {
+        let kind = Kind::Dir(BTreeMap::new());
+        let root = Node {
+            name: "",
+            parent: ROOT,
+            kind,
+        };
+        Self { nodes: vec![root] }
+    }
 
-    fn execute(&mut self, entry: Entry) {
-        match entry {
-            Entry::Exit => {
-                let size = self.stack.pop().unwrap();
-                self.stack.last_mut().map(|v| *v += size);
-                (self.on_exit)(size)
+    /// Builds the tree from the commands and their output, along with the
+    /// anomalies found in it
+    ///
+    /// Directories are created when they are first seen, either listed or
+    /// entered, and files listed more than once keep the last size.
+    fn parse(input: &'a str) -> (Self, Vec<Warning>) {
+        let mut fs = Self::new();
+        let mut warnings = vec![];
+        let mut cwd = ROOT;
+        for (line, text) in input.lines().enumerate() {
+            let mut warn = |anomaly| {
+                warnings.push(Warning {
+                    line: line + 1,
+                    anomaly,
+                })
+            };
+            let Some(entry) = Entry::parse(text) else {
+                warn(Anomaly::Unknown(text.to_string()));
+                continue;
+            };
+            let inserted = match entry {
+                Entry::Cd(path) => {
+                    cwd = fs.cd(cwd, path, &mut warn);
+                    continue;
+                }
+                Entry::Dir(name) => fs.insert(cwd, name, Kind::Dir(BTreeMap::new())),
+                Entry::File(size, name) => fs.insert(cwd, name, Kind::File(size)),
+                Entry::List => continue,
+            };
+            if let Err(anomaly) = inserted {
+                warn(anomaly)
             }
-            Entry::File(size) => {
-                self.stack.last_mut().map(|v| *v += size);
-                self.used += size
+        }
+        (fs, warnings)
+    }
+
+    /// Directory reached by following the path from `cwd`, creating the
+    /// directories that don't exist yet
+    fn cd(&mut self, cwd: usize, path: &'a str, warn: &mut impl FnMut(Anomaly)) -> usize {
+        let mut dir = if path.starts_with('/') { ROOT } else { cwd };
+        for name in path.split('/') {
+            dir = match name {
+                "" | "." => dir,
+                ".." if dir == ROOT => {
+                    warn(Anomaly::AboveRoot);
+                    ROOT
+                }
+                ".." => self.nodes[dir].parent,
+                name => match self.insert(dir, name, Kind::Dir(BTreeMap::new())) {
+                    Ok(child) => child,
+                    Err(anomaly) => {
+                        warn(anomaly);
+                        return dir;
+                    }
+                },
+            };
+        }
+        dir
+    }
+
+    /// Adds a new node to the directory and returns its index, if a node
+    /// with the same name is already there it's updated instead
+    fn insert(&mut self, dir: usize, name: &'a str, kind: Kind<'a>) -> Result<usize, Anomaly> {
+        let new = self.nodes.len();
+        let Kind::Dir(children) = &mut self.nodes[dir].kind else {
+            unreachable!("nodes are only added to directories")
+        };
+        let idx = *children.entry(name).or_insert(new);
+        if idx == new {
+            let parent = dir;
+            self.nodes.push(Node { name, parent, kind });
+            return Ok(idx);
+        }
+        match (&mut self.nodes[idx].kind, kind) {
+            (Kind::Dir(_), Kind::Dir(_)) => Ok(idx),
+            (Kind::File(old), Kind::File(new)) if *old == new => Ok(idx),
+            (Kind::File(old), Kind::File(new)) => {
+                let old = std::mem::replace(old, new);
+                let path = self.path(idx);
+                Err(Anomaly::SizeConflict { path, old, new })
             }
-            Entry::Enter => self.stack.push(0),
-            Entry::Root => self.stack.drain(..).rev().for_each(&mut self.on_exit),
-            _ => (),
+            (Kind::File(_), Kind::Dir(_)) => Err(Anomaly::NotADirectory(self.path(idx))),
+            (Kind::Dir(_), Kind::File(_)) => Err(Anomaly::IsADirectory(self.path(idx))),
         }
     }
+
+    /// Finds the node at the given absolute path
+    fn lookup(&self, path: &str) -> Option<usize> {
+        let path = path.strip_prefix('/')?;
+        path.split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(ROOT, |dir, name| match &self.nodes[dir].kind {
+                Kind::Dir(children) => children.get(name).copied(),
+                Kind::File(_) => None,
+            })
+    }
+
+    /// Absolute path of the node
+    fn path(&self, mut node: usize) -> String {
+        let mut names = vec![];
+        while node != ROOT {
+            names.push(self.nodes[node].name);
+            node = self.nodes[node].parent;
+        }
+        if names.is_empty() {
+            return "/".to_string();
+        }
+        names.iter().rev().map(|name| format!("/{name}")).collect()
+    }
+
+    fn is_dir(&self, node: usize) -> bool {
+        matches!(self.nodes[node].kind, Kind::Dir(_))
+    }
+
+    /// Recursive size of every node
+    fn du(&self) -> Vec<usize> {
+        let mut sizes: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|node| match node.kind {
+                Kind::File(size) => size,
+                Kind::Dir(_) => 0,
+            })
+            .collect();
+        for (idx, node) in self.nodes.iter().enumerate().skip(1).rev() {
+            sizes[node.parent] += sizes[idx];
+        }
+        sizes
+    }
+
+    /// Directories along with their recursive size
+    fn dirs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let sizes = self.du();
+        (0..self.nodes.len())
+            .filter(|&node| self.is_dir(node))
+            .map(move |node| (node, sizes[node]))
+    }
+
+    /// Total size of the directories of at most 100000
+    fn small_total(&self) -> usize {
+        self.dirs()
+            .map(|(_, size)| size)
+            .filter(|&size| size <= 100000)
+            .sum()
+    }
+
+    /// Smallest directory to delete to have enough free space for the update,
+    /// `None` if there's already enough
+    fn to_delete(&self) -> Option<(usize, usize)> {
+        let missing = (self.du()[ROOT] + 30000000).saturating_sub(70000000);
+        if missing == 0 {
+            return None;
+        }
+        self.dirs()
+            .filter(|&(_, size)| size >= missing)
+            .min_by_key(|&(_, size)| size)
+    }
+
+    fn fmt_node(&self, f: &mut fmt::Formatter<'_>, node: usize, depth: usize) -> fmt::Result {
+        let Node { name, kind, .. } = &self.nodes[node];
+        let name = if node == ROOT { "/" } else { name };
+        match kind {
+            Kind::File(size) => writeln!(f, "{:w$}- {name} (file, size={size})", "", w = depth * 2),
+            Kind::Dir(children) => {
+                writeln!(f, "{:w$}- {name} (dir)", "", w = depth * 2)?;
+                children
+                    .values()
+                    .try_for_each(|&child| self.fmt_node(f, child, depth + 1))
+            }
+        }
+    }
+
+    /// Tree of the directories and files, with the directories that count
+    /// towards the first answer highlighted
+    fn dot(&self) -> Dot {
+        let mut dot = Dot::new();
+        let sizes = self.du();
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let name = if idx == ROOT { "/" } else { node.name };
+            let slash = if self.is_dir(idx) && idx != ROOT {
+                "/"
+            } else {
+                ""
+            };
+            dot.node(format!("{name}{slash}\n{}", sizes[idx]));
+            if idx != ROOT {
+                dot.edge(node.parent, idx);
+            }
+        }
+        let small = self.dirs().filter(|&(_, size)| size <= 100000);
+        dot.highlight(small.map(|(node, _)| node), "lightblue");
+        dot
+    }
 }
 
-fn entries(input: &str) -> impl Iterator<Item = Entry> + '_ {
-    input
-        .lines()
-        .map(Entry::parse)
-        .map(Option::unwrap)
-        .chain(once(Entry::Root))
+impl fmt::Display for Fs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_node(f, ROOT, 0)
+    }
 }
 
 fn solve_1(input: &str) -> usize {
-    let mut sum = 0;
-    let mut fs = Fs::new(|size| sum += (size <= 100000) as usize * size);
-    entries(input).for_each(|entry| fs.execute(entry));
-    sum
+    Fs::parse(input).0.small_total()
 }
 
-fn solve_2(input: &str) -> usize {
-    let mut ordered = vec![];
-    let mut fs = Fs::new(|size| {
-        let Err(idx) = ordered.binary_search(&size) else { return };
-        ordered.insert(idx, size)
-    });
-    entries(input).for_each(|entry| fs.execute(entry));
-    let missing = 30000000 - (70000000 - fs.used);
-    let (Ok(idx) | Err(idx)) = ordered.binary_search(&missing);
-    ordered[idx]
+/// Path and size of the directory to delete
+fn solve_2(input: &str) -> Option<(String, usize)> {
+    let (fs, _) = Fs::parse(input);
+    let (dir, size) = fs.to_delete()?;
+    Some((fs.path(dir), size))
 }
 
 fn main() {
-    println!("Answer 1: {}", solve_1(INPUT));
-    println!("Answer 2: {}", solve_2(INPUT));
+    let (fs, warnings) = Fs::parse(INPUT);
+    warnings.iter().for_each(|w| eprintln!("{w}"));
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("--dot") => print!("{}", fs.dot()),
+        Some("--tree") => print!("{fs}"),
+        Some("--du") => {
+            let sizes = fs.du();
+            for path in args {
+                match fs.lookup(&path) {
+                    Some(node) => println!("{}\t{path}", sizes[node]),
+                    None => println!("{path}: not found"),
+                }
+            }
+        }
+        _ => {
+            println!("Answer 1: {}", fs.small_total());
+            match fs.to_delete() {
+                Some((dir, size)) => println!("Answer 2: {size} ({})", fs.path(dir)),
+                None => println!("Answer 2: nothing to delete"),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -119,9 +356,87 @@ mod tests {
         5626152 d.ext\n\
         7214296 k\n";
 
+    const TREE: &str = "\
+        - / (dir)\n  \
+          - a (dir)\n    \
+            - e (dir)\n      \
+              - i (file, size=584)\n    \
+            - f (file, size=29116)\n    \
+            - g (file, size=2557)\n    \
+            - h.lst (file, size=62596)\n  \
+          - b.txt (file, size=14848514)\n  \
+          - c.dat (file, size=8504156)\n  \
+          - d (dir)\n    \
+            - d.ext (file, size=5626152)\n    \
+            - d.log (file, size=8033020)\n    \
+            - j (file, size=4060174)\n    \
+            - k (file, size=7214296)\n";
+
     #[test]
     fn test() {
         assert_eq!(super::solve_1(TEST_INPUT), 95437);
-        assert_eq!(super::solve_2(TEST_INPUT), 24933642);
+        assert_eq!(super::solve_2(TEST_INPUT), Some(("/d".into(), 24933642)));
+        assert_eq!(super::solve_2("$ cd /\n$ ls\n40000000 a\n"), None);
+        let full = "$ cd /\n$ ls\n80000000 a\n";
+        assert_eq!(super::solve_2(full), Some(("/".into(), 80000000)));
+    }
+
+    #[test]
+    fn tree() {
+        let (fs, warnings) = super::Fs::parse(TEST_INPUT);
+        assert_eq!(warnings, []);
+        assert_eq!(fs.to_string(), TREE);
+        let e = fs.lookup("/a/e").unwrap();
+        assert_eq!(fs.path(e), "/a/e");
+        assert_eq!(fs.du()[e], 584);
+        assert_eq!(fs.lookup("/a/x"), None);
+    }
+
+    #[test]
+    fn anomalies() {
+        use super::{Anomaly::*, Warning};
+
+        let input = "\
+            $ cd /a/b\n\
+            $ ls\n\
+            12 34\n\
+            dir c\n\
+            3 c\n\
+            $ cd ../../..\n\
+            $ ls\n\
+            dir a\n\
+            $ cd a/b/./c\n\
+            $ ls\n\
+            7 x\n\
+            $ ls\n\
+            7 x\n\
+            9 x\n\
+            dir x\n\
+            $ cd x\n\
+            $ rm -rf /\n";
+        let (fs, warnings) = super::Fs::parse(input);
+        let warning = |line, anomaly| Warning { line, anomaly };
+        let path = "/a/b/c/x".to_string();
+        let expected = [
+            warning(5, IsADirectory("/a/b/c".to_string())),
+            warning(6, AboveRoot),
+            warning(
+                14,
+                SizeConflict {
+                    path: path.clone(),
+                    old: 7,
+                    new: 9,
+                },
+            ),
+            warning(15, NotADirectory(path.clone())),
+            warning(16, NotADirectory(path)),
+            warning(17, Unknown("$ rm -rf /".to_string())),
+        ];
+        assert_eq!(warnings, expected);
+        assert_eq!(warnings[1].to_string(), "line 6: `cd ..` above the root");
+
+        let sizes = fs.du();
+        assert_eq!(sizes[fs.lookup("/a/b/34").unwrap()], 12);
+        assert_eq!(sizes[fs.lookup("/a").unwrap()], 21);
     }
 }
@@ -45,12 +45,13 @@ pub const INPUT_LINES_ARRAY: [&[u8]; INPUT_LINES] = {
 
 pub const fn max_depth(input: &str) -> usize {
     let mut depth = 0;
-    let mut at = 0;
+    let mut at: usize = 0;
     for_each_line!(@cached INPUT_LINES_ARRAY, |line| {
-        let Some(entry) = Entry::parse(line) else { panic!() };
+        // lines that are not recognized don't change the depth
+        let Some(entry) = Entry::parse(line) else { continue };
         match entry {
             Entry::Enter => at += 1,
-            Entry::Exit => at -= 1,
+            Entry::Exit => at = at.saturating_sub(1),
             Entry::Root => at = 0,
             _ => (),
         }
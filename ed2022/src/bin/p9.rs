@@ -1,174 +1,126 @@
-use std::{
-    collections::HashSet,
-    iter::successors,
-    ops::{Add, AddAssign, Sub},
-    str::FromStr,
+use aoc::{
+    geometry::Point,
+    turtle::{self, Action, Command, Heading, Language},
 };
+use std::collections::HashSet;
 
 const INPUT: &str = include_str!("../../assets/p9.in");
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
-struct Knot<T>(T, T);
-
-impl<T> Knot<T> {
-    fn map<O>(self, f: impl FnOnce(T) -> O + Copy) -> Knot<O> {
-        Knot(f(self.0), f(self.1))
-    }
-
-    fn zip<U>(self, other: Knot<U>) -> Knot<(T, U)> {
-        Knot((self.0, other.0), (self.1, other.1))
-    }
-}
-
-impl<A, B> Knot<(A, B)> {
-    fn map_unzip<O>(self, f: impl FnOnce(A, B) -> O + Copy) -> Knot<O> {
-        Knot(f(self.0 .0, self.0 .1), f(self.1 .0, self.1 .1))
-    }
-}
-
-impl<T: Add> Add for Knot<T> {
-    type Output = Knot<T::Output>;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        self.zip(rhs).map_unzip(T::add)
-    }
-}
-
-impl<T: Add + Copy + From<T::Output>> AddAssign for Knot<T> {
-    fn add_assign(&mut self, rhs: Self) {
-        *self = (*self + rhs).map(<_>::from)
-    }
-}
-
-impl<T: Sub> Sub for Knot<T> {
-    type Output = Knot<T::Output>;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        self.zip(rhs).map_unzip(T::sub)
+/// Motions of the head: a direction (`R`, `U`, `L`, `D` or a diagonal like
+/// `UR`) or a step vector (like `2,-1`) followed by the number of times to move
+fn motions(input: &str) -> Vec<Command> {
+    let step = |x, y| Action::Move(Point::new(x, y));
+    let language = Language::new(&[
+        ("R", step(1, 0)),
+        ("U", step(0, 1)),
+        ("L", step(-1, 0)),
+        ("D", step(0, -1)),
+        ("UR", step(1, 1)),
+        ("RU", step(1, 1)),
+        ("UL", step(-1, 1)),
+        ("LU", step(-1, 1)),
+        ("DR", step(1, -1)),
+        ("RD", step(1, -1)),
+        ("DL", step(-1, -1)),
+        ("LD", step(-1, -1)),
+    ]);
+    language.with_vectors().parse_all(input).unwrap()
+}
+
+/// Rope made of any number of knots, the first one being the head
+struct Rope {
+    knots: Vec<Point>,
+    /// Positions visited by each knot
+    visited: Vec<HashSet<Point>>,
+}
+
+impl Rope {
+    fn new(len: usize) -> Self {
+        assert!(len > 0, "a rope needs at least the head");
+        Self {
+            knots: vec![Point::default(); len],
+            visited: vec![HashSet::from([Point::default()]); len],
+        }
     }
-}
-
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
-enum Move {
-    Forward,
-    Backward,
-}
 
-impl Move {
-    fn to_unit(self) -> isize {
-        match self {
-            Self::Forward => 1,
-            Self::Backward => -1,
+    /// Moves the knot one position at a time towards the previous one, until
+    /// they touch
+    fn follow(&mut self, at: usize) {
+        loop {
+            let (prev, knot) = (self.knots[at - 1], self.knots[at]);
+            let (dx, dy) = (prev.x - knot.x, prev.y - knot.y);
+            if dx.abs() <= 1 && dy.abs() <= 1 {
+                return;
+            }
+            self.knots[at] = Point::new(knot.x + dx.signum(), knot.y + dy.signum());
+            self.visited[at].insert(self.knots[at]);
         }
     }
-}
-
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
-enum Direction<T> {
-    Vertical(T),
-    Horizontal(T),
-}
 
-impl<T> Direction<T> {
-    fn map<O>(self, f: impl FnOnce(T) -> O) -> Direction<O> {
-        match self {
-            Direction::Vertical(v) => Direction::Vertical(f(v)),
-            Direction::Horizontal(v) => Direction::Horizontal(f(v)),
-        }
+    /// Moves the head to the given position, next to the current one
+    fn step(&mut self, head: Point) {
+        self.knots[0] = head;
+        self.visited[0].insert(head);
+        (1..self.knots.len()).for_each(|at| self.follow(at));
     }
-}
 
-impl Direction<isize> {
-    fn moves(self) -> impl Iterator<Item = Direction<Move>> {
-        use Direction::*;
-        let filter0 = |d| (!matches!(d, Vertical(0) | Horizontal(0))).then_some(d);
-        successors(filter0(self), move |&dir| match dir {
-            Vertical(0) | Horizontal(0) => unreachable!(),
-            Vertical(1..) | Horizontal(1..) => filter0(dir.map(|n| n - 1)),
-            Vertical(_) | Horizontal(_) => filter0(dir.map(|n| n + 1)),
-        })
-        .map(|dir| match dir {
-            Vertical(0) | Horizontal(0) => unreachable!(),
-            Vertical(1..) | Horizontal(1..) => dir.map(|_| Move::Forward),
-            Vertical(_) | Horizontal(_) => dir.map(|_| Move::Backward),
-        })
+    fn tail_visited(&self) -> usize {
+        self.visited.last().unwrap().len()
     }
-}
 
-impl FromStr for Direction<isize> {
-    type Err = <isize as FromStr>::Err;
-
-    fn from_str(line: &str) -> Result<Self, Self::Err> {
-        if let Some(n) = line.strip_prefix("R ") {
-            Ok(Self::Horizontal(isize::from_str(n)?))
-        } else if let Some(n) = line.strip_prefix("U ") {
-            Ok(Self::Vertical(isize::from_str(n)?))
-        } else if let Some(n) = line.strip_prefix("L ") {
-            Ok(Self::Horizontal(-isize::from_str(n)?))
-        } else if let Some(n) = line.strip_prefix("D ") {
-            Ok(Self::Vertical(-isize::from_str(n)?))
-        } else {
-            isize::from_str("").map(|_| unreachable!())
+    /// Draws the positions visited by the knot, with `s` marking the start,
+    /// `None` if the rope doesn't have it
+    fn trail(&self, knot: usize) -> Option<String> {
+        let visited = self.visited.get(knot)?;
+        let xs = visited.iter().map(|k| k.x);
+        let ys = visited.iter().map(|k| k.y);
+        let [min_x, max_x] = [xs.clone().min(), xs.max()].map(Option::unwrap);
+        let [min_y, max_y] = [ys.clone().min(), ys.max()].map(Option::unwrap);
+
+        let mut trail = String::new();
+        for y in (min_y..=max_y).rev() {
+            for x in min_x..=max_x {
+                trail.push(match Point::new(x, y) {
+                    Point { x: 0, y: 0 } => 's',
+                    k if visited.contains(&k) => '#',
+                    _ => '.',
+                });
+            }
+            trail.push('\n');
         }
+        Some(trail)
     }
 }
 
-fn directions(input: &str) -> impl Iterator<Item = Direction<isize>> + '_ {
-    input.lines().map(str::parse).map(Result::unwrap)
+fn simulate(input: &str, knots: usize) -> Rope {
+    let mut rope = Rope::new(knots);
+    let mut head = Heading::new(Point::new(0, 0), Point::new(0, 0));
+    let path = turtle::trajectory(&mut head, &motions(input));
+    path.into_iter().skip(1).for_each(|p| rope.step(p));
+    rope
 }
 
-struct Rope<const LENGTH: usize> {
-    head: Knot<isize>,
-    visited: HashSet<Knot<isize>>,
-    knots: [Knot<isize>; LENGTH],
-}
-
-impl<const LENGTH: usize> Rope<LENGTH> {
-    fn start() -> Self {
-        Self {
-            head: Knot(0, 0),
-            visited: HashSet::from([Knot(0, 0)]),
-            knots: [Knot(0, 0); LENGTH],
-        }
-    }
-
-    fn move_tail(&mut self, at: usize, prev: Knot<isize>) {
-        let k = &mut self.knots[at];
-        match prev - *k {
-            o @ Knot(2 | -2, 0) => k.0 += o.0 / 2,
-            o @ Knot(0, 2 | -2) => k.1 += o.1 / 2,
-            o @ Knot(2 | -2, 1 | -1) => *k += Knot(o.0 / 2, o.1),
-            o @ Knot(1 | -1, 2 | -2) => *k += Knot(o.0, o.1 / 2),
-            o @ Knot(2 | -2, 2 | -2) => *k += o.map(|v| v / 2),
-            _ => return,
-        }
-        if at + 1 < LENGTH {
-            self.move_tail(at + 1, self.knots[at])
-        } else {
-            self.visited.insert(self.knots[at]);
-        }
-    }
-
-    fn move_head(&mut self, direction: Direction<Move>) {
-        match direction {
-            Direction::Horizontal(m) => self.head.0 += m.to_unit(),
-            Direction::Vertical(m) => self.head.1 += m.to_unit(),
-        }
-        self.move_tail(0, self.head)
-    }
+fn solve_1(input: &str) -> usize {
+    simulate(input, 2).tail_visited()
 }
 
-fn solve<const LENGTH: usize>(input: &str) -> usize {
-    let mut state = Rope::<LENGTH>::start();
-    for m in directions(input).flat_map(Direction::moves) {
-        state.move_head(m)
-    }
-    state.visited.len()
+fn solve_2(input: &str) -> usize {
+    simulate(input, 10).tail_visited()
 }
 
 fn main() {
-    println!("Answer 1: {}", solve::<1>(INPUT));
-    println!("Answer 2: {}", solve::<9>(INPUT));
+    let mut args = std::env::args().skip(1);
+    if let Some("--trail") = args.next().as_deref() {
+        let knot = args.next().map_or(Ok(9), |k| k.parse::<usize>());
+        match knot.map(|k| (k, simulate(INPUT, 10).trail(k))) {
+            Ok((_, Some(trail))) => print!("{trail}"),
+            Ok((knot, None)) => eprintln!("no knot {knot}, the rope has knots from 0 to 9"),
+            Err(err) => eprintln!("invalid knot, {err}"),
+        }
+        return;
+    }
+    println!("Answer 1: {}", solve_1(INPUT));
+    println!("Answer 2: {}", solve_2(INPUT));
 }
 
 #[cfg(test)]
@@ -195,8 +147,34 @@ mod tests {
 
     #[test]
     fn test() {
-        assert_eq!(super::solve::<1>(TEST_INPUT), 13);
-        assert_eq!(super::solve::<9>(TEST_INPUT), 1);
-        assert_eq!(super::solve::<9>(SECOND_TEST_INPUT), 36);
+        assert_eq!(super::solve_1(TEST_INPUT), 13);
+        assert_eq!(super::solve_2(TEST_INPUT), 1);
+        assert_eq!(super::solve_2(SECOND_TEST_INPUT), 36);
+    }
+
+    #[test]
+    fn trail() {
+        let rope = super::simulate(TEST_INPUT, 2);
+        let trail = "\
+            ..##.\n\
+            ...##\n\
+            .####\n\
+            ....#\n\
+            s###.\n";
+        assert_eq!(rope.trail(1).as_deref(), Some(trail));
+        assert_eq!(rope.trail(2), None);
+        assert_eq!(rope.visited[0].len(), 21);
+    }
+
+    #[test]
+    fn steps() {
+        use aoc::geometry::Point;
+
+        let rope = super::simulate("UR 3\nDL 1\n", 2);
+        assert_eq!(rope.tail_visited(), 3);
+        let rope = super::simulate("3,0 1\n0,-2 2\n", 3);
+        let knots = [(3, -4), (3, -3), (3, -2)].map(|(x, y)| Point::new(x, y));
+        assert_eq!(rope.knots, knots);
+        assert_eq!(rope.visited[2].len(), 4);
     }
 }
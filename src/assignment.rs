@@ -0,0 +1,183 @@
+//! Solver for puzzles where each item has to be assigned to a different slot,
+//! knowing which slots each item can go in.
+
+use std::fmt;
+
+/// Reason why the assignment has no single solution
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Unsolvable {
+    /// No assignment satisfies the constraints
+    Impossible,
+    /// There is more than one assignment, two of which are given
+    Ambiguous([Vec<usize>; 2]),
+}
+
+impl fmt::Display for Unsolvable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unsolvable::Impossible => write!(f, "no assignment is possible"),
+            Unsolvable::Ambiguous([a, b]) => {
+                write!(
+                    f,
+                    "the assignment is ambiguous, both {a:?} and {b:?} are possible"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Unsolvable {}
+
+/// Slots in which each of N items can go, among N slots
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidates {
+    /// Whether item `i` can go in slot `s`, at `allowed[i][s]`
+    allowed: Vec<Vec<bool>>,
+}
+
+impl Candidates {
+    /// Candidates where any item can go in any slot
+    pub fn new(n: usize) -> Self {
+        Self {
+            allowed: vec![vec![true; n]; n],
+        }
+    }
+
+    /// Builds the candidates from a function telling whether `item` can go
+    /// in `slot`
+    pub fn from_fn(n: usize, allowed: impl Fn(usize, usize) -> bool) -> Self {
+        let allowed = (0..n).map(|i| (0..n).map(|s| allowed(i, s)).collect());
+        Self {
+            allowed: allowed.collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.allowed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_empty()
+    }
+
+    pub fn is_allowed(&self, item: usize, slot: usize) -> bool {
+        self.allowed[item][slot]
+    }
+
+    /// Forbids `item` from going in `slot`
+    pub fn remove(&mut self, item: usize, slot: usize) {
+        self.allowed[item][slot] = false;
+    }
+
+    /// Only keeps the slots of `item` for which `keep` returns true
+    pub fn retain(&mut self, item: usize, keep: impl Fn(usize) -> bool) {
+        for (slot, allowed) in self.allowed[item].iter_mut().enumerate() {
+            *allowed &= keep(slot);
+        }
+    }
+
+    /// Slots in which `item` can go
+    pub fn slots(&self, item: usize) -> impl Iterator<Item = usize> + '_ {
+        let allowed = self.allowed[item].iter().enumerate();
+        allowed.filter_map(|(slot, &a)| a.then_some(slot))
+    }
+
+    /// Only slot of `item`, if it can go in exactly one
+    fn single_slot(&self, item: usize) -> Option<usize> {
+        let mut slots = self.slots(item);
+        slots.next().filter(|_| slots.next().is_none())
+    }
+
+    /// Removes the candidates that can't be part of any solution because an
+    /// item only fits in one slot or a slot only fits one item
+    ///
+    /// Returns false if an item or a slot is left without candidates.
+    pub fn propagate(&mut self) -> bool {
+        let n = self.len();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for item in 0..n {
+                let Some(slot) = self.single_slot(item) else {
+                    if self.slots(item).next().is_none() {
+                        return false;
+                    }
+                    continue;
+                };
+                for other in (0..n).filter(|&o| o != item) {
+                    changed |= std::mem::replace(&mut self.allowed[other][slot], false);
+                }
+            }
+            for slot in 0..n {
+                let mut items = (0..n).filter(|&i| self.allowed[i][slot]);
+                let Some(item) = items.next() else {
+                    return false;
+                };
+                if items.next().is_none() {
+                    for other in (0..n).filter(|&s| s != slot) {
+                        changed |= std::mem::replace(&mut self.allowed[item][other], false);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Finds up to `limit` solutions, propagating the constraints and then
+    /// trying every slot of the item with the fewest candidates
+    fn search(mut self, limit: usize, solutions: &mut Vec<Vec<usize>>) {
+        if !self.propagate() {
+            return;
+        }
+        let open = (0..self.len())
+            .map(|item| (self.slots(item).count(), item))
+            .filter(|&(count, _)| count > 1)
+            .min();
+        let Some((_, item)) = open else {
+            let solution = (0..self.len()).map(|i| self.single_slot(i).unwrap());
+            return solutions.push(solution.collect());
+        };
+        for slot in self.slots(item).collect::<Vec<_>>() {
+            if solutions.len() >= limit {
+                return;
+            }
+            let mut guess = self.clone();
+            guess.retain(item, |s| s == slot);
+            guess.search(limit, solutions);
+        }
+    }
+
+    /// The only assignment of the items to the slots, with the slot of each
+    /// item at its index
+    pub fn solve(&self) -> Result<Vec<usize>, Unsolvable> {
+        let mut solutions = vec![];
+        self.clone().search(2, &mut solutions);
+        match <[_; 2]>::try_from(solutions) {
+            Ok(two) => Err(Unsolvable::Ambiguous(two)),
+            Err(mut one) => one.pop().ok_or(Unsolvable::Impossible),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Candidates, Unsolvable};
+
+    #[test]
+    fn test() {
+        // only solvable by propagation
+        let candidates = Candidates::from_fn(4, |i, s| s <= i);
+        assert_eq!(candidates.solve(), Ok(vec![0, 1, 2, 3]));
+
+        // needs to guess, and the guesses lead to two solutions
+        let mut candidates = Candidates::new(3);
+        candidates.remove(2, 0);
+        candidates.remove(2, 1);
+        let [a, b] = [vec![0, 1, 2], vec![1, 0, 2]];
+        assert_eq!(candidates.solve(), Err(Unsolvable::Ambiguous([a, b])));
+
+        // two items that only fit in the same slot
+        let candidates = Candidates::from_fn(3, |i, s| if i < 2 { s == 0 } else { true });
+        assert_eq!(candidates.solve(), Err(Unsolvable::Impossible));
+    }
+}
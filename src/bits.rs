@@ -0,0 +1,287 @@
+//! Rows of bits of any width, written as lines of `0` and `1`.
+
+use std::{fmt, str::FromStr};
+
+/// Sequence of bits, read from left to right like a binary number
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bits {
+    len: usize,
+    /// Bits from the left, 64 per word starting from the most significant
+    words: Vec<u64>,
+}
+
+impl Bits {
+    /// Sequence of `len` zeros
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "bit out of range");
+        self.words[i / 64] >> (63 - i % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, bit: bool) {
+        assert!(i < self.len, "bit out of range");
+        let mask = 1 << (63 - i % 64);
+        match bit {
+            true => self.words[i / 64] |= mask,
+            false => self.words[i / 64] &= !mask,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    /// Flips all the bits
+    pub fn not(&self) -> Self {
+        let mut not = Self::new(self.len);
+        (0..self.len).for_each(|i| not.set(i, !self.get(i)));
+        not
+    }
+
+    /// Value of the bits as a binary number, if it fits
+    pub fn to_u128(&self) -> Option<u128> {
+        self.iter().try_fold(0u128, |acc, bit| {
+            acc.checked_mul(2).map(|acc| acc + bit as u128)
+        })
+    }
+}
+
+impl FromIterator<bool> for Bits {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        let bits: Vec<_> = iter.into_iter().collect();
+        let mut res = Self::new(bits.len());
+        bits.into_iter()
+            .enumerate()
+            .for_each(|(i, b)| res.set(i, b));
+        res
+    }
+}
+
+/// Error found while parsing, lines and columns start from 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A character other than `0` and `1`
+    InvalidChar { line: usize, column: usize },
+    /// A row with a different width than the first one
+    RaggedRow { line: usize, width: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Error::InvalidChar { line, column } => {
+                write!(f, "line {line}, column {column}: expected 0 or 1")
+            }
+            Error::RaggedRow { line, width } => {
+                write!(f, "line {line}: expected a row of {width} bits")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Bits {
+    fn parse_line(s: &str, line: usize) -> Result<Self, Error> {
+        s.chars()
+            .zip(1..)
+            .map(|(c, column)| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(Error::InvalidChar { line, column }),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Bits {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_line(s, 1)
+    }
+}
+
+impl fmt::Display for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter()
+            .try_for_each(|b| write!(f, "{}", if b { '1' } else { '0' }))
+    }
+}
+
+/// Rows of bits that all have the same width
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BitMatrix {
+    width: usize,
+    rows: Vec<Bits>,
+}
+
+impl BitMatrix {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn rows(&self) -> &[Bits] {
+        &self.rows
+    }
+
+    /// Number of ones in each column
+    pub fn column_ones(&self) -> Vec<usize> {
+        let mut ones = vec![0; self.width];
+        for row in &self.rows {
+            row.iter()
+                .zip(&mut ones)
+                .for_each(|(b, o)| *o += b as usize);
+        }
+        ones
+    }
+
+    pub fn trie(&self) -> BitTrie {
+        let mut trie = BitTrie::default();
+        self.rows.iter().for_each(|row| trie.insert(row));
+        trie
+    }
+}
+
+/// Parses a line for each row, the lines must all have the same length
+impl FromStr for BitMatrix {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = s
+            .lines()
+            .zip(1..)
+            .map(|(l, line)| Bits::parse_line(l, line));
+        let rows: Vec<Bits> = rows.collect::<Result<_, _>>()?;
+        let width = rows.first().map_or(0, Bits::len);
+        if let Some(idx) = rows.iter().position(|r| r.len() != width) {
+            return Err(Error::RaggedRow {
+                line: idx + 1,
+                width,
+            });
+        }
+        Ok(Self { width, rows })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Node {
+    /// Number of sequences going through the node
+    count: usize,
+    children: [Option<usize>; 2],
+}
+
+/// Binary trie that counts the sequences of bits starting with each prefix
+#[derive(Clone, Debug)]
+pub struct BitTrie {
+    nodes: Vec<Node>,
+}
+
+impl Default for BitTrie {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node::default()],
+        }
+    }
+}
+
+impl BitTrie {
+    pub fn insert(&mut self, bits: &Bits) {
+        let mut node = 0;
+        self.nodes[0].count += 1;
+        for bit in bits.iter() {
+            node = match self.nodes[node].children[bit as usize] {
+                Some(child) => child,
+                None => {
+                    self.nodes.push(Node::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children[bit as usize] = Some(child);
+                    child
+                }
+            };
+            self.nodes[node].count += 1;
+        }
+    }
+
+    fn count_of(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |n| self.nodes[n].count)
+    }
+
+    /// Number of sequences starting with the prefix
+    pub fn count(&self, prefix: impl IntoIterator<Item = bool>) -> usize {
+        let mut node = Some(0);
+        for bit in prefix {
+            node = node.and_then(|n| self.nodes[n].children[bit as usize]);
+        }
+        self.count_of(node)
+    }
+
+    /// Follows a sequence from the root, choosing at each step the bit
+    /// returned by `choose`, which gets the number of sequences continuing
+    /// with a zero and with a one, as long as both are possible
+    pub fn select(&self, choose: impl Fn(usize, usize) -> bool) -> Bits {
+        let mut bits = vec![];
+        let mut node = 0;
+        loop {
+            let [zero, one] = self.nodes[node].children;
+            let bit = match (zero, one) {
+                (None, None) => break,
+                (Some(_), None) => false,
+                (None, Some(_)) => true,
+                (zero, one) => choose(self.count_of(zero), self.count_of(one)),
+            };
+            bits.push(bit);
+            node = self.nodes[node].children[bit as usize].unwrap();
+        }
+        bits.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitMatrix, Bits, Error};
+
+    #[test]
+    fn test() {
+        let wide: Bits = format!("1{}1", "0".repeat(98)).parse().unwrap();
+        assert_eq!(wide.len(), 100);
+        assert_eq!(wide.to_u128(), Some(1 << 99 | 1));
+        assert_eq!(wide.not().to_string(), format!("0{}0", "1".repeat(98)));
+        let error = Error::InvalidChar { line: 1, column: 3 };
+        assert_eq!("012".parse::<Bits>(), Err(error));
+
+        let matrix: BitMatrix = "0110\n0011\n1010\n0111".parse().unwrap();
+        assert_eq!((matrix.width(), matrix.height()), (4, 4));
+        assert_eq!(matrix.column_ones(), [1, 2, 4, 2]);
+        let error = Error::RaggedRow { line: 3, width: 2 };
+        assert_eq!("01\n10\n011".parse::<BitMatrix>(), Err(error));
+        assert_eq!(error.to_string(), "line 3: expected a row of 2 bits");
+        let error = Error::InvalidChar { line: 2, column: 1 };
+        assert_eq!("01\n 1".parse::<BitMatrix>(), Err(error));
+
+        let trie = matrix.trie();
+        assert_eq!(trie.count([]), 4);
+        assert_eq!(trie.count([false, true]), 2);
+        assert_eq!(trie.count([true, true]), 0);
+        let most = trie.select(|zeros, ones| ones > zeros);
+        assert_eq!(most.to_string(), "0110");
+    }
+}
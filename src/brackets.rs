@@ -0,0 +1,149 @@
+//! Matching of opening and closing delimiters, like brackets, in a text.
+
+/// Closing delimiter that doesn't match the last unclosed opening one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Corrupt {
+    /// Byte offset of the delimiter in the text
+    pub offset: usize,
+    pub found: char,
+    /// Closing delimiter of the last unclosed one, `None` if all were closed
+    pub expected: Option<char>,
+}
+
+/// Outcome of checking a text stopping at the first error
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Check {
+    /// All the delimiters are closed
+    Complete,
+    /// Some delimiters aren't closed, and closing them in order makes the
+    /// text complete
+    Incomplete {
+        completion: String,
+    },
+    Corrupt(Corrupt),
+}
+
+/// Outcome of checking a whole text, continuing after the errors
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub errors: Vec<Corrupt>,
+    /// Closing delimiters needed to complete the text, ignoring the errors
+    pub completion: String,
+}
+
+/// Table of pairs of opening and closing delimiters, every other character
+/// is ignored
+#[derive(Clone, Debug)]
+pub struct Delimiters {
+    pairs: Vec<(char, char)>,
+}
+
+impl Delimiters {
+    pub fn new(pairs: &[(char, char)]) -> Self {
+        Self {
+            pairs: pairs.to_vec(),
+        }
+    }
+
+    /// Index of the pair of the delimiter, and whether it's the opening one
+    pub fn pair_of(&self, c: char) -> Option<(usize, bool)> {
+        self.pairs
+            .iter()
+            .enumerate()
+            .find_map(|(i, &(open, close))| match c {
+                _ if c == open => Some((i, true)),
+                _ if c == close => Some((i, false)),
+                _ => None,
+            })
+    }
+
+    /// Walks the text with a stack of the indices of the unclosed pairs,
+    /// calling `on_error` for every mismatch, which returns false to stop
+    fn walk(&self, text: &str, mut on_error: impl FnMut(Corrupt) -> bool) -> Vec<usize> {
+        let mut open = vec![];
+        for (offset, found) in text.char_indices() {
+            match self.pair_of(found) {
+                None => {}
+                Some((pair, true)) => open.push(pair),
+                Some((pair, false)) if open.last() == Some(&pair) => {
+                    open.pop();
+                }
+                Some((_, false)) => {
+                    let expected = open.last().map(|&p| self.pairs[p].1);
+                    let error = Corrupt {
+                        offset,
+                        found,
+                        expected,
+                    };
+                    if !on_error(error) {
+                        break;
+                    }
+                    // taken as a mistyped closing of the last one, so that
+                    // the following errors aren't caused by this one
+                    open.pop();
+                }
+            }
+        }
+        open
+    }
+
+    fn completion(&self, open: Vec<usize>) -> String {
+        open.into_iter().rev().map(|p| self.pairs[p].1).collect()
+    }
+
+    /// Checks the text up to the first mismatched delimiter
+    pub fn check(&self, text: &str) -> Check {
+        let mut error = None;
+        let open = self.walk(text, |e| {
+            error = Some(e);
+            false
+        });
+        match error {
+            Some(error) => Check::Corrupt(error),
+            None if open.is_empty() => Check::Complete,
+            None => Check::Incomplete {
+                completion: self.completion(open),
+            },
+        }
+    }
+
+    /// Checks the whole text, reporting all the mismatched delimiters
+    pub fn check_all(&self, text: &str) -> Report {
+        let mut errors = vec![];
+        let open = self.walk(text, |e| {
+            errors.push(e);
+            true
+        });
+        let completion = self.completion(open);
+        Report { errors, completion }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Check, Corrupt, Delimiters};
+
+    #[test]
+    fn test() {
+        let delimiters = Delimiters::new(&[('(', ')'), ('[', ']'), ('«', '»')]);
+        assert_eq!(delimiters.check("a(b[c]d)"), Check::Complete);
+        let completion = "])".to_string();
+        assert_eq!(delimiters.check("(«»["), Check::Incomplete { completion });
+        let error = Corrupt {
+            offset: 3,
+            found: ')',
+            expected: Some('»'),
+        };
+        assert_eq!(delimiters.check("(«)»"), Check::Corrupt(error));
+
+        let report = delimiters.check_all("(«)] x [)(");
+        let errors = [(4, ']', Some(')')), (9, ')', Some(']'))];
+        let errors = errors.map(|(offset, found, expected)| Corrupt {
+            offset,
+            found,
+            expected,
+        });
+        assert_eq!(report.errors, [error, errors[0], errors[1]]);
+        assert_eq!(report.completion, ")");
+    }
+}
@@ -0,0 +1,63 @@
+//! Minimisation of convex functions of an integer, like the total distance
+//! of a set of points from a position.
+
+use std::ops::RangeInclusive;
+
+/// Position in the range where the convex function `cost` is lowest, the
+/// first one if there are many, together with its cost
+///
+/// Binary searches the first position where the slope isn't negative, so it
+/// only evaluates `cost` a logarithmic number of times.
+pub fn minimize<T: Ord>(range: RangeInclusive<i64>, cost: impl Fn(i64) -> T) -> (i64, T) {
+    let (mut lo, mut hi) = range.into_inner();
+    assert!(lo <= hi, "empty range");
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        // a convex function keeps growing after it starts to
+        if cost(mid) <= cost(mid + 1) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    (lo, cost(lo))
+}
+
+/// A position that minimises the sum of the distances from the values,
+/// reordering them
+pub fn median(values: &mut [i64]) -> i64 {
+    assert!(!values.is_empty(), "no values");
+    let mid = (values.len() - 1) / 2;
+    *values.select_nth_unstable(mid).1
+}
+
+/// Range of positions, around the mean of the values, that contains the one
+/// minimising the sum of the triangular numbers of the distances from them
+///
+/// The minimum of that cost is within half a unit of the mean, so the range
+/// only needs to contain the mean rounded down and the two positions next
+/// to it.
+pub fn around_mean(values: &[i64]) -> RangeInclusive<i64> {
+    assert!(!values.is_empty(), "no values");
+    let sum: i128 = values.iter().map(|&v| v as i128).sum();
+    let mean = sum.div_euclid(values.len() as i128) as i64;
+    mean - 1..=mean + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{around_mean, median, minimize};
+
+    #[test]
+    fn test() {
+        assert_eq!(minimize(-100..=100, |x| (x - 7).pow(2)), (7, 0));
+        assert_eq!(minimize(-100..=100, |x| (x - 7).abs().max(3)), (4, 3));
+        assert_eq!(minimize(10..=100, |x| x), (10, 10));
+        assert_eq!(minimize(5..=5, |x| -x), (5, -5));
+
+        let mut values = [9, -3, 4, 1, 1, 20];
+        assert_eq!(median(&mut values), 1);
+        assert_eq!(around_mean(&values), 4..=6);
+        assert_eq!(around_mean(&[-3, -4]), -5..=-3);
+    }
+}
@@ -0,0 +1,141 @@
+//! Writer of graphs in the Graphviz DOT language, to look at them with
+//! `dot -Tsvg`.
+
+use std::fmt::{self, Display};
+
+use crate::graph::Graph;
+
+#[derive(Clone, Debug)]
+struct Node {
+    label: String,
+    color: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+struct Edge {
+    from: usize,
+    to: usize,
+    label: Option<String>,
+}
+
+/// Directed graph that is written in the DOT language by its [`Display`]
+/// implementation
+///
+/// Nodes are referred to by the index returned when adding them.
+#[derive(Clone, Debug, Default)]
+pub struct Dot {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+impl Dot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Converts the graph labeling the nodes with their name and the edges
+    /// with their weight
+    pub fn from_graph<W: Display>(graph: &Graph<W>) -> Self {
+        let mut dot = Self::new();
+        for node in 0..graph.len() {
+            dot.node(graph.name(node));
+        }
+        for from in 0..graph.len() {
+            for (to, weight) in graph.edges(from) {
+                dot.labeled_edge(from, *to, weight);
+            }
+        }
+        dot
+    }
+
+    pub fn node(&mut self, label: impl Display) -> usize {
+        self.nodes.push(Node {
+            label: label.to_string(),
+            color: None,
+        });
+        self.nodes.len() - 1
+    }
+
+    pub fn relabel(&mut self, node: usize, label: impl Display) {
+        self.nodes[node].label = label.to_string();
+    }
+
+    pub fn edge(&mut self, from: usize, to: usize) {
+        let label = None;
+        self.edges.push(Edge { from, to, label });
+    }
+
+    pub fn labeled_edge(&mut self, from: usize, to: usize, label: impl Display) {
+        let label = Some(label.to_string());
+        self.edges.push(Edge { from, to, label });
+    }
+
+    /// Fills the nodes with the given color, which can be any color name or
+    /// `#rrggbb` value supported by Graphviz
+    pub fn highlight(&mut self, nodes: impl IntoIterator<Item = usize>, color: &str) {
+        for node in nodes {
+            self.nodes[node].color = Some(color.to_string());
+        }
+    }
+}
+
+/// Quoted DOT string, where new lines become line breaks
+struct Quoted<'a>(&'a str);
+
+impl Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+impl Display for Dot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "digraph {{")?;
+        for (idx, node) in self.nodes.iter().enumerate() {
+            write!(f, "    n{idx} [label={}", Quoted(&node.label))?;
+            if let Some(color) = &node.color {
+                write!(f, ", style=filled, fillcolor={}", Quoted(color))?;
+            }
+            writeln!(f, "];")?;
+        }
+        for Edge { from, to, label } in &self.edges {
+            write!(f, "    n{from} -> n{to}")?;
+            if let Some(label) = label {
+                write!(f, " [label={}]", Quoted(label))?;
+            }
+            writeln!(f, ";")?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dot;
+
+    #[test]
+    fn test() {
+        let mut dot = Dot::new();
+        let [a, b] = ["a", "say \"b\"\n"].map(|l| dot.node(l));
+        dot.edge(a, b);
+        dot.labeled_edge(b, a, 3);
+        dot.highlight([b], "gold");
+        let expected = "\
+            digraph {\n    \
+                n0 [label=\"a\"];\n    \
+                n1 [label=\"say \\\"b\\\"\\n\", style=filled, fillcolor=\"gold\"];\n    \
+                n0 -> n1;\n    \
+                n1 -> n0 [label=\"3\"];\n\
+            }\n";
+        assert_eq!(dot.to_string(), expected);
+    }
+}
@@ -0,0 +1,417 @@
+//! Flood fill and connected components on collections of cells of the
+//! plane, like dense grids or sparse regions, where a predicate tells which
+//! cells are passable.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// Cells that are considered adjacent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Only the cells sharing a side
+    Four,
+    /// The cells sharing a side or a corner
+    Eight,
+}
+
+impl Connectivity {
+    fn offsets(self) -> &'static [(isize, isize)] {
+        const FOUR: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+        const EIGHT: [(isize, isize); 8] = [
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (1, -1),
+            (-1, 1),
+            (1, 1),
+        ];
+        match self {
+            Connectivity::Four => &FOUR,
+            Connectivity::Eight => &EIGHT,
+        }
+    }
+}
+
+/// Collection of cells, identified by their `(x, y)` position, that the
+/// fills go through
+pub trait Cells {
+    type Coord: Copy + Ord + Hash;
+
+    /// Every cell, components are labelled in this order
+    fn cells(&self) -> impl Iterator<Item = (Self::Coord, Self::Coord)>;
+
+    /// Cells adjacent to the given one that are part of the collection
+    fn neighbours(
+        &self,
+        cell: (Self::Coord, Self::Coord),
+    ) -> impl Iterator<Item = (Self::Coord, Self::Coord)>;
+
+    /// Cells where the fills from outside of the collection go in
+    fn border(&self) -> impl Iterator<Item = (Self::Coord, Self::Coord)>;
+}
+
+/// Goes through the passable cells reachable from any of the `starts`,
+/// calling `visit` on each one, which returns whether the cell was reached
+/// for the first time
+pub fn fill<C: Cells>(
+    cells: &C,
+    starts: impl IntoIterator<Item = (C::Coord, C::Coord)>,
+    passable: impl Fn((C::Coord, C::Coord)) -> bool,
+    mut visit: impl FnMut((C::Coord, C::Coord)) -> bool,
+) {
+    let mut stack: Vec<_> = starts.into_iter().collect();
+    while let Some(cell) = stack.pop() {
+        if passable(cell) && visit(cell) {
+            stack.extend(cells.neighbours(cell));
+        }
+    }
+}
+
+/// Finds the groups of connected passable cells, in the order of their
+/// first cell, calling `label` with each cell and the index of its group,
+/// which returns whether the cell wasn't labelled yet
+pub fn label<C: Cells>(
+    cells: &C,
+    passable: impl Fn((C::Coord, C::Coord)) -> bool,
+    mut label: impl FnMut((C::Coord, C::Coord), usize) -> bool,
+) -> Vec<Component<C::Coord>> {
+    let mut components = vec![];
+    for cell in cells.cells() {
+        let index = components.len();
+        let mut component = Component {
+            size: 0,
+            min: cell,
+            max: cell,
+        };
+        let visit = |(x, y)| {
+            if !label((x, y), index) {
+                return false;
+            }
+            component.size += 1;
+            component.min = (component.min.0.min(x), component.min.1.min(y));
+            component.max = (component.max.0.max(x), component.max.1.max(y));
+            true
+        };
+        fill(cells, [cell], &passable, visit);
+        if component.size > 0 {
+            components.push(component);
+        }
+    }
+    components
+}
+
+/// Group of passable cells connected to each other
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Component<T = usize> {
+    pub size: usize,
+    /// Top left corner of the bounding box
+    pub min: (T, T),
+    /// Bottom right corner of the bounding box, included
+    pub max: (T, T),
+}
+
+/// Components of a collection of cells, with the component of every cell
+#[derive(Clone, Debug)]
+pub struct Components<T = usize, L = Vec<Option<usize>>> {
+    pub components: Vec<Component<T>>,
+    /// Index of the component of each passable cell, for grids a vector row
+    /// by row with `None` for the cells that aren't passable
+    pub labels: L,
+}
+
+/// Size of a grid whose cells are identified by their `(x, y)` position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+    pub connectivity: Connectivity,
+}
+
+impl Grid {
+    pub fn new(width: usize, height: usize, connectivity: Connectivity) -> Self {
+        Self {
+            width,
+            height,
+            connectivity,
+        }
+    }
+
+    pub fn index(&self, (x, y): (usize, usize)) -> usize {
+        x + y * self.width
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Cells adjacent to the given one that are inside of the grid
+    pub fn neighbours(&self, (x, y): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width, self.height);
+        self.connectivity
+            .offsets()
+            .iter()
+            .filter_map(move |&(dx, dy)| {
+                let x = x.checked_add_signed(dx).filter(|&x| x < width)?;
+                let y = y.checked_add_signed(dy).filter(|&y| y < height)?;
+                Some((x, y))
+            })
+    }
+
+    /// Fills from the starts, marking the reached cells row by row
+    fn fill_from(
+        &self,
+        starts: impl IntoIterator<Item = (usize, usize)>,
+        passable: impl Fn((usize, usize)) -> bool,
+    ) -> Vec<bool> {
+        let mut seen = vec![false; self.width * self.height];
+        let visit = |cell| !std::mem::replace(&mut seen[self.index(cell)], true);
+        fill(self, starts, passable, visit);
+        seen
+    }
+
+    /// Marks the passable cells reachable from `start`, row by row
+    pub fn flood_fill(
+        &self,
+        start: (usize, usize),
+        passable: impl Fn((usize, usize)) -> bool,
+    ) -> Vec<bool> {
+        self.fill_from([start], passable)
+    }
+
+    /// Marks the passable cells reachable from outside of the grid, going
+    /// in through the passable cells on the border
+    pub fn fill_from_outside(&self, passable: impl Fn((usize, usize)) -> bool) -> Vec<bool> {
+        self.fill_from(Cells::border(self), passable)
+    }
+
+    /// Labels the groups of connected passable cells, in the order of their
+    /// first cell row by row
+    pub fn components(&self, passable: impl Fn((usize, usize)) -> bool) -> Components {
+        let mut labels = vec![None; self.width * self.height];
+        let components = label(self, passable, |cell, index| {
+            let label = &mut labels[self.index(cell)];
+            if label.is_some() {
+                return false;
+            }
+            *label = Some(index);
+            true
+        });
+        Components { components, labels }
+    }
+}
+
+impl Cells for Grid {
+    type Coord = usize;
+
+    fn cells(&self) -> impl Iterator<Item = (usize, usize)> {
+        Grid::cells(self)
+    }
+
+    fn neighbours(&self, cell: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        Grid::neighbours(self, cell)
+    }
+
+    fn border(&self) -> impl Iterator<Item = (usize, usize)> {
+        let (w, h) = (self.width, self.height);
+        let rows = (0..w).flat_map(move |x| [(x, 0), (x, h.saturating_sub(1))]);
+        let cols = (0..h).flat_map(move |y| [(0, y), (w.saturating_sub(1), y)]);
+        rows.chain(cols).filter(move |_| w > 0 && h > 0)
+    }
+}
+
+/// Rectangle of the plane at any position, whose cells are only stored when
+/// reached, for cells given sparsely like a set of the blocked ones
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    /// Top left corner
+    pub min: (i64, i64),
+    /// Bottom right corner, included
+    pub max: (i64, i64),
+    pub connectivity: Connectivity,
+}
+
+impl Region {
+    pub fn new(min: (i64, i64), max: (i64, i64), connectivity: Connectivity) -> Self {
+        Self {
+            min,
+            max,
+            connectivity,
+        }
+    }
+
+    /// Bounding box of the cells grown by one, so that its border goes
+    /// around them, the box is around the origin if there are no cells
+    pub fn around<'a>(
+        cells: impl IntoIterator<Item = &'a (i64, i64)>,
+        connectivity: Connectivity,
+    ) -> Self {
+        let (min, max) = cells
+            .into_iter()
+            .fold(None, |bounds, &(x, y)| match bounds {
+                None => Some(((x, y), (x, y))),
+                Some(((x0, y0), (x1, y1))) => {
+                    Some(((x0.min(x), y0.min(y)), (x1.max(x), y1.max(y))))
+                }
+            })
+            .unwrap_or(((0, 0), (0, 0)));
+        let (min, max) = ((min.0 - 1, min.1 - 1), (max.0 + 1, max.1 + 1));
+        Self::new(min, max, connectivity)
+    }
+
+    fn contains(&self, (x, y): (i64, i64)) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y)
+    }
+
+    fn fill_from(
+        &self,
+        starts: impl IntoIterator<Item = (i64, i64)>,
+        passable: impl Fn((i64, i64)) -> bool,
+    ) -> HashSet<(i64, i64)> {
+        let mut seen = HashSet::new();
+        fill(self, starts, passable, |cell| seen.insert(cell));
+        seen
+    }
+
+    /// Passable cells reachable from `start`
+    pub fn flood_fill(
+        &self,
+        start: (i64, i64),
+        passable: impl Fn((i64, i64)) -> bool,
+    ) -> HashSet<(i64, i64)> {
+        self.fill_from([start].into_iter().filter(|&c| self.contains(c)), passable)
+    }
+
+    /// Passable cells reachable from outside of the region, going in through
+    /// the passable cells on the border
+    pub fn fill_from_outside(&self, passable: impl Fn((i64, i64)) -> bool) -> HashSet<(i64, i64)> {
+        self.fill_from(Cells::border(self), passable)
+    }
+
+    /// Labels the groups of connected passable cells, in the order of their
+    /// first cell row by row
+    pub fn components(
+        &self,
+        passable: impl Fn((i64, i64)) -> bool,
+    ) -> Components<i64, HashMap<(i64, i64), usize>> {
+        let mut labels = HashMap::new();
+        let components = label(self, passable, |cell, index| {
+            if labels.contains_key(&cell) {
+                return false;
+            }
+            labels.insert(cell, index);
+            true
+        });
+        Components { components, labels }
+    }
+}
+
+impl Cells for Region {
+    type Coord = i64;
+
+    fn cells(&self) -> impl Iterator<Item = (i64, i64)> {
+        let (min, max) = (self.min, self.max);
+        (min.1..=max.1).flat_map(move |y| (min.0..=max.0).map(move |x| (x, y)))
+    }
+
+    fn neighbours(&self, (x, y): (i64, i64)) -> impl Iterator<Item = (i64, i64)> {
+        let offsets = self.connectivity.offsets().iter();
+        offsets
+            .map(move |&(dx, dy)| (x + dx as i64, y + dy as i64))
+            .filter(|&cell| self.contains(cell))
+    }
+
+    fn border(&self) -> impl Iterator<Item = (i64, i64)> {
+        let (min, max) = (self.min, self.max);
+        let rows = (min.0..=max.0).flat_map(move |x| [(x, min.1), (x, max.1)]);
+        let cols = (min.1..=max.1).flat_map(move |y| [(min.0, y), (max.0, y)]);
+        rows.chain(cols)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Component, Connectivity, Grid, Region};
+    use std::collections::HashSet;
+
+    const MAP: [&str; 5] = ["#..##", "#.#..", "##.#.", "..##.", "####."];
+
+    fn passable((x, y): (usize, usize)) -> bool {
+        MAP[y].as_bytes()[x] == b'.'
+    }
+
+    #[test]
+    fn components() {
+        let grid = Grid::new(5, 5, Connectivity::Four);
+        let components = grid.components(passable);
+        let sizes: Vec<_> = components.components.iter().map(|c| c.size).collect();
+        assert_eq!(sizes, [3, 5, 1, 2]);
+        let last = Component {
+            size: 2,
+            min: (0, 3),
+            max: (1, 3),
+        };
+        assert_eq!(components.components[3], last);
+        assert_eq!(components.labels[grid.index((4, 4))], Some(1));
+
+        let grid = Grid::new(5, 5, Connectivity::Eight);
+        let sizes: Vec<_> = grid
+            .components(passable)
+            .components
+            .iter()
+            .map(|c| c.size)
+            .collect();
+        assert_eq!(sizes, [11]);
+    }
+
+    #[test]
+    fn fill() {
+        let grid = Grid::new(5, 5, Connectivity::Four);
+        let filled = grid.flood_fill((1, 0), passable);
+        assert_eq!(filled.iter().filter(|&&f| f).count(), 3);
+        assert!(filled[grid.index((1, 1))] && !filled[grid.index((3, 1))]);
+        assert_eq!(grid.flood_fill((0, 0), passable), [false; 25]);
+
+        // the cell at (2, 2) is enclosed
+        let outside = grid.fill_from_outside(passable);
+        assert_eq!(outside.iter().filter(|&&o| o).count(), 10);
+        assert!(!outside[grid.index((2, 2))]);
+    }
+
+    #[test]
+    fn region() {
+        // the map moved to negative coordinates, without building a grid
+        let passable = |(x, y): (i64, i64)| passable(((x + 10) as usize, (y + 20) as usize));
+        let region = Region::new((-10, -20), (-6, -16), Connectivity::Four);
+        let components = region.components(passable);
+        let sizes: Vec<_> = components.components.iter().map(|c| c.size).collect();
+        assert_eq!(sizes, [3, 5, 1, 2]);
+        assert_eq!(components.components[3].min, (-10, -17));
+        assert_eq!(components.labels.get(&(-6, -16)), Some(&1));
+        assert_eq!(region.flood_fill((-9, -20), passable).len(), 3);
+        assert!(region.flood_fill((0, 0), |_| true).is_empty());
+
+        // walls enclosing the cell at (1, 1), the air around them is outside
+        let walls = [
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (0, 1),
+            (2, 1),
+            (3, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+        ];
+        let walls: HashSet<_> = walls.into_iter().collect();
+        let region = Region::around(&walls, Connectivity::Four);
+        assert_eq!((region.min, region.max), ((-1, -1), (4, 3)));
+        let outside = region.fill_from_outside(|cell| !walls.contains(&cell));
+        assert_eq!(outside.len(), 6 * 5 - walls.len() - 1);
+        assert!(outside.contains(&(3, 0)) && !outside.contains(&(1, 1)));
+    }
+}
@@ -0,0 +1,297 @@
+//! Points and segments on the integer lattice.
+
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+}
+
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Closed segment between two lattice points, which can also be equal
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Segment {
+    pub a: Point,
+    pub b: Point,
+}
+
+/// Line through lattice points, identified by its smallest step, going
+/// right or up, and by `step.y * x - step.x * y` which is the same for all
+/// of its points
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Line {
+    step: (i64, i64),
+    offset: i64,
+}
+
+impl Line {
+    /// Position of a lattice point of the line, counted in steps
+    fn position(&self, p: Point) -> i64 {
+        match self.step {
+            (0, _) => p.y,
+            (sx, _) => p.x.div_euclid(sx),
+        }
+    }
+}
+
+impl Segment {
+    pub fn new(a: Point, b: Point) -> Self {
+        Self { a, b }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.a.y == self.b.y
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.a.x == self.b.x
+    }
+
+    /// Whether the segment is at 45 degrees
+    pub fn is_diagonal(&self) -> bool {
+        self.a.x.abs_diff(self.b.x) == self.a.y.abs_diff(self.b.y)
+    }
+
+    /// Number of steps between the lattice points of the segment
+    fn steps(&self) -> u64 {
+        gcd(self.a.x.abs_diff(self.b.x), self.a.y.abs_diff(self.b.y))
+    }
+
+    /// Smallest step from `a` towards `b` that lands on a lattice point,
+    /// `(0, 0)` if the ends are equal
+    pub fn step(&self) -> (i64, i64) {
+        let (dx, dy) = (self.b.x - self.a.x, self.b.y - self.a.y);
+        match self.steps() as i64 {
+            0 => (0, 0),
+            g => (dx / g, dy / g),
+        }
+    }
+
+    /// Number of lattice points on the segment, ends included
+    pub fn len(&self) -> u64 {
+        self.steps() + 1
+    }
+
+    /// A segment always has at least one point
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Lattice points of the segment going from `a` to `b`
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let (a, (sx, sy)) = (self.a, self.step());
+        (0..self.len() as i64).map(move |k| Point::new(a.x + k * sx, a.y + k * sy))
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        let (a, b) = (self.a, self.b);
+        let cross =
+            (b.x - a.x) as i128 * (p.y - a.y) as i128 - (b.y - a.y) as i128 * (p.x - a.x) as i128;
+        cross == 0
+            && (a.x.min(b.x)..=a.x.max(b.x)).contains(&p.x)
+            && (a.y.min(b.y)..=a.y.max(b.y)).contains(&p.y)
+    }
+
+    fn line(&self) -> Option<Line> {
+        let step = match self.step() {
+            (0, 0) => return None,
+            (sx, sy) if sx < 0 || (sx == 0 && sy < 0) => (-sx, -sy),
+            step => step,
+        };
+        let offset = step.1 * self.a.x - step.0 * self.a.y;
+        Some(Line { step, offset })
+    }
+
+    /// Only lattice point in common with a segment that isn't on the same
+    /// line, if any
+    fn crossing(&self, other: &Segment) -> Option<Point> {
+        let (d1, d2) = (self.step(), other.step());
+        let (a1, a2) = (self.a, other.a);
+        let cross = d1.0 as i128 * d2.1 as i128 - d1.1 as i128 * d2.0 as i128;
+        let p = if d1 == (0, 0) {
+            a1
+        } else if d2 == (0, 0) {
+            a2
+        } else if cross == 0 {
+            return None;
+        } else {
+            // a1 + t * d1 on the line of the other segment
+            let t = (a2.x - a1.x) as i128 * d2.1 as i128 - (a2.y - a1.y) as i128 * d2.0 as i128;
+            if t % cross != 0 {
+                return None;
+            }
+            let t = (t / cross) as i64;
+            Point::new(a1.x + t * d1.0, a1.y + t * d1.1)
+        };
+        (self.contains(p) && other.contains(p)).then_some(p)
+    }
+}
+
+/// Ranges of positions on a line covered by at least two of the ranges,
+/// sorted and disjoint
+fn overlaps(mut ranges: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    let mut events: Vec<_> = ranges
+        .iter()
+        .flat_map(|&(s, e)| [(s, 1), (e + 1, -1)])
+        .collect();
+    ranges.clear();
+    events.sort_unstable();
+    let mut covered = 0;
+    for (at, change) in events {
+        let before = covered;
+        covered += change;
+        if before < 2 && covered >= 2 {
+            ranges.push((at, at));
+        } else if before >= 2 && covered < 2 {
+            ranges.last_mut().unwrap().1 = at - 1;
+        }
+    }
+    ranges
+}
+
+/// Number of lattice points covered by at least two of the segments
+///
+/// The segments are swept from left to right and only the pairs whose
+/// bounding boxes overlap are compared: two segments on the same line can
+/// have many points in common, which are counted for each line as a whole,
+/// while other pairs meet at most in one point.
+pub fn count_overlaps(segments: &[Segment]) -> u64 {
+    let mut lines = HashMap::<Line, Vec<(i64, i64)>>::new();
+    for s in segments {
+        if let Some(line) = s.line() {
+            let (a, b) = (line.position(s.a), line.position(s.b));
+            lines.entry(line).or_default().push((a.min(b), a.max(b)));
+        }
+    }
+    let lines: HashMap<_, _> = lines
+        .into_iter()
+        .map(|(line, ranges)| (line, overlaps(ranges)))
+        .filter(|(_, overlaps)| !overlaps.is_empty())
+        .collect();
+    let on_lines: u64 = lines
+        .values()
+        .flatten()
+        .map(|(s, e)| (e - s + 1) as u64)
+        .sum();
+
+    let min_x = |s: &Segment| s.a.x.min(s.b.x);
+    let mut order: Vec<_> = segments.iter().collect();
+    order.sort_unstable_by_key(|s| min_x(s));
+    // lines of the segments meeting at each crossing point
+    let mut crossings = HashMap::<Point, HashSet<Option<Line>>>::new();
+    let mut active: Vec<&Segment> = vec![];
+    for s in order {
+        active.retain(|a| a.a.x.max(a.b.x) >= min_x(s));
+        for other in &active {
+            let (l1, l2) = (s.line(), other.line());
+            if l1.is_some() && l1 == l2 {
+                continue;
+            }
+            if let Some(p) = s.crossing(other) {
+                crossings.entry(p).or_default().extend([l1, l2]);
+            }
+        }
+        active.push(s);
+    }
+    // a point on an overlap is covered by a segment of that line, which
+    // also crosses every other segment there, so it's possible to know on how
+    // many overlaps, already counted once each, the crossing points are
+    let mut count = on_lines;
+    for (p, through) in crossings {
+        let on_overlaps = through.iter().flatten().filter(|line| {
+            let overlaps = lines.get(line).map_or(&[][..], Vec::as_slice);
+            let pos = line.position(p);
+            let idx = overlaps.partition_point(|&(_, e)| e < pos);
+            overlaps.get(idx).is_some_and(|&(s, _)| s <= pos)
+        });
+        match on_overlaps.count() as u64 {
+            0 => count += 1,
+            n => count -= n - 1,
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_overlaps, Point, Segment};
+    use std::collections::HashMap;
+
+    fn segment(ax: i64, ay: i64, bx: i64, by: i64) -> Segment {
+        Segment::new(Point::new(ax, ay), Point::new(bx, by))
+    }
+
+    fn brute_force(segments: &[Segment]) -> u64 {
+        let mut covered = HashMap::<_, u64>::new();
+        for p in segments.iter().flat_map(Segment::points) {
+            *covered.entry(p).or_default() += 1;
+        }
+        covered.values().filter(|&&c| c >= 2).count() as u64
+    }
+
+    #[test]
+    fn points() {
+        let s = segment(1, 7, 7, -2);
+        assert_eq!(s.step(), (2, -3));
+        let points: Vec<_> = s.points().map(|p| (p.x, p.y)).collect();
+        assert_eq!(points, [(1, 7), (3, 4), (5, 1), (7, -2)]);
+        assert!(s.contains(Point::new(5, 1)) && !s.contains(Point::new(9, -5)));
+        assert_eq!(segment(3, 3, 3, 3).points().count(), 1);
+    }
+
+    #[test]
+    fn overlaps() {
+        let segments = [
+            segment(0, 0, 10, 0),
+            segment(5, 0, 20, 0),
+            segment(12, 0, 30, 0),
+            segment(6, -2, 6, 4),
+            segment(0, -6, 12, 3),
+            segment(8, 0, 8, 0),
+            segment(3, 3, 3, 3),
+            segment(3, 3, 3, 3),
+        ];
+        assert_eq!(count_overlaps(&segments), brute_force(&segments));
+
+        // overlaps on two lines that meet
+        let segments = [
+            segment(0, 0, 4, 4),
+            segment(2, 2, 6, 6),
+            segment(2, 0, 2, 4),
+            segment(2, 5, 2, 1),
+        ];
+        assert_eq!(count_overlaps(&segments), 6);
+
+        let mut seed = 3u64;
+        let mut random = |n| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) as i64 % n
+        };
+        for len in 0..100 {
+            let segments: Vec<_> = (0..len)
+                .map(|_| [0; 4].map(|_| random(12)))
+                .map(|[ax, ay, bx, by]| segment(ax, ay, bx, by))
+                .collect();
+            assert_eq!(count_overlaps(&segments), brute_force(&segments));
+        }
+
+        let far = [
+            segment(0, 0, 4_000_000, 4_000_000),
+            segment(2_000_000, 2_000_000, 8_000_000, 8_000_000),
+        ];
+        assert_eq!(count_overlaps(&far), 2_000_001);
+    }
+}
@@ -0,0 +1,194 @@
+//! Directed graphs with weighted edges whose nodes are identified by name.
+
+use std::collections::HashMap;
+
+/// Directed graph with edges of weight `W`
+///
+/// Nodes are interned by name and referred to by their index, which is
+/// assigned in order of insertion. Every edge is also stored in reverse so
+/// that the graph can be walked in both directions.
+#[derive(Clone, Debug)]
+pub struct Graph<W> {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+    edges: Vec<Vec<(usize, W)>>,
+    reverse: Vec<Vec<(usize, W)>>,
+}
+
+impl<W> Default for Graph<W> {
+    fn default() -> Self {
+        Self {
+            names: vec![],
+            indices: HashMap::new(),
+            edges: vec![],
+            reverse: vec![],
+        }
+    }
+}
+
+impl<W> Graph<W> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Index of the node with the given name, adding it if it's new
+    pub fn node(&mut self, name: &str) -> usize {
+        if let Some(&idx) = self.indices.get(name) {
+            return idx;
+        }
+        let idx = self.names.len();
+        self.names.push(name.to_string());
+        self.indices.insert(name.to_string(), idx);
+        self.edges.push(vec![]);
+        self.reverse.push(vec![]);
+        idx
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
+
+    pub fn name(&self, node: usize) -> &str {
+        &self.names[node]
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: W)
+    where
+        W: Clone,
+    {
+        self.edges[from].push((to, weight.clone()));
+        self.reverse[to].push((from, weight));
+    }
+
+    /// Outgoing edges of the node, as pairs of destination and weight
+    pub fn edges(&self, node: usize) -> &[(usize, W)] {
+        &self.edges[node]
+    }
+
+    /// Incoming edges of the node, as pairs of source and weight
+    pub fn reverse_edges(&self, node: usize) -> &[(usize, W)] {
+        &self.reverse[node]
+    }
+
+    fn walk(adjacency: &[Vec<(usize, W)>], start: usize) -> Vec<bool> {
+        let mut seen = vec![false; adjacency.len()];
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            if !std::mem::replace(&mut seen[node], true) {
+                stack.extend(adjacency[node].iter().map(|(n, _)| n));
+            }
+        }
+        seen
+    }
+
+    /// Marks the nodes that can be reached from `start`, itself included
+    pub fn reachable_from(&self, start: usize) -> Vec<bool> {
+        Self::walk(&self.edges, start)
+    }
+
+    /// Marks the nodes from which `end` can be reached, itself included
+    pub fn reaching(&self, end: usize) -> Vec<bool> {
+        Self::walk(&self.reverse, end)
+    }
+
+    /// Orders the nodes so that every edge goes from a node to one that
+    /// comes after it, or returns the nodes of a cycle in edge order
+    pub fn topological_order(&self) -> Result<Vec<usize>, Vec<usize>> {
+        let mut incoming: Vec<_> = self.reverse.iter().map(Vec::len).collect();
+        let mut order: Vec<_> = (0..self.len()).filter(|&n| incoming[n] == 0).collect();
+        let mut at = 0;
+        while let Some(&node) = order.get(at) {
+            at += 1;
+            for &(next, _) in &self.edges[node] {
+                incoming[next] -= 1;
+                if incoming[next] == 0 {
+                    order.push(next);
+                }
+            }
+        }
+        if order.len() == self.len() {
+            return Ok(order);
+        }
+        // every node left has a predecessor that is also left, so going
+        // backwards from any of them eventually gets back to a visited one
+        let mut node = (0..self.len()).find(|&n| incoming[n] != 0).unwrap();
+        let mut visited = vec![None; self.len()];
+        let mut path = vec![];
+        while visited[node].is_none() {
+            visited[node] = Some(path.len());
+            path.push(node);
+            node = self.reverse[node]
+                .iter()
+                .map(|&(prev, _)| prev)
+                .find(|&prev| incoming[prev] != 0)
+                .unwrap();
+        }
+        let mut cycle = path.split_off(visited[node].unwrap());
+        cycle.reverse();
+        Err(cycle)
+    }
+
+    /// Computes a value for every node from the values of the nodes its edges
+    /// go to, evaluating each node only once
+    ///
+    /// `f` is called with the node and the list of its outgoing edges paired
+    /// with the value of their destination. Fails with the nodes of a cycle
+    /// if the graph isn't acyclic.
+    pub fn aggregate<T>(
+        &self,
+        mut f: impl FnMut(usize, &[(&W, &T)]) -> T,
+    ) -> Result<Vec<T>, Vec<usize>> {
+        let order = self.topological_order()?;
+        let mut values: Vec<Option<T>> = (0..self.len()).map(|_| None).collect();
+        for &node in order.iter().rev() {
+            let children: Vec<_> = self.edges[node]
+                .iter()
+                .map(|(n, w)| (w, values[*n].as_ref().unwrap()))
+                .collect();
+            values[node] = Some(f(node, &children));
+        }
+        Ok(values.into_iter().map(Option::unwrap).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Graph;
+
+    fn graph(edges: &[(&str, &str, usize)]) -> Graph<usize> {
+        let mut graph = Graph::new();
+        for &(from, to, w) in edges {
+            let [from, to] = [from, to].map(|n| graph.node(n));
+            graph.add_edge(from, to, w);
+        }
+        graph
+    }
+
+    #[test]
+    fn aggregate() {
+        let graph = graph(&[("a", "b", 2), ("a", "c", 1), ("b", "c", 3), ("d", "a", 1)]);
+        let total = graph
+            .aggregate(|_, edges| edges.iter().map(|(&w, &t)| w * (1 + t)).sum::<usize>())
+            .unwrap();
+        assert_eq!(total, [9, 3, 0, 10]);
+
+        let c = graph.index("c").unwrap();
+        let reaching = graph.reaching(c).into_iter().filter(|&r| r).count();
+        assert_eq!(reaching, 4);
+        assert_eq!(graph.reachable_from(c), [false, false, true, false]);
+    }
+
+    #[test]
+    fn cycle() {
+        let graph = graph(&[("a", "b", 1), ("b", "c", 1), ("c", "b", 1), ("c", "d", 1)]);
+        assert_eq!(graph.topological_order(), Err(vec![2, 1]));
+    }
+}
@@ -0,0 +1,438 @@
+//! Minimal JSON parser, where numbers can only be integers.
+
+use std::{fmt, str::FromStr};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<Value>),
+    /// Members in the order they are written
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Value of the first member of the object with the given name
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Values directly inside of an array or an object
+    pub fn children(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
+        match self {
+            Value::Array(array) => Box::new(array.iter()),
+            Value::Object(members) => Box::new(members.iter().map(|(_, v)| v)),
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Visits the value and everything inside of it depth first, the values
+    /// inside are skipped when `f` returns false
+    pub fn walk<'a>(&'a self, f: &mut impl FnMut(&'a Value) -> bool) {
+        if f(self) {
+            self.children().for_each(|v| v.walk(f))
+        }
+    }
+
+    /// Sum of all the numbers inside of the value
+    pub fn sum_numbers(&self) -> i64 {
+        let mut sum = 0;
+        self.walk(&mut |v| {
+            sum += v.as_i64().unwrap_or(0);
+            true
+        });
+        sum
+    }
+
+    /// Sum of all the numbers inside of the value, ignoring the objects that
+    /// have a member with the given value and everything inside of them
+    pub fn sum_numbers_skipping(&self, skip: &Value) -> i64 {
+        let mut sum = 0;
+        self.walk(&mut |v| match v {
+            Value::Object(members) => members.iter().all(|(_, v)| v != skip),
+            v => {
+                sum += v.as_i64().unwrap_or(0);
+                true
+            }
+        });
+        sum
+    }
+}
+
+struct Quoted<'a>(&'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                '\t' => write!(f, "\\t")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+/// Writes the value without any whitespace
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Number(n) => write!(f, "{n}"),
+            Value::String(s) => write!(f, "{}", Quoted(s)),
+            Value::Array(array) => {
+                write!(f, "[")?;
+                for (i, v) in array.iter().enumerate() {
+                    let sep = if i > 0 { "," } else { "" };
+                    write!(f, "{sep}{v}")?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (name, v)) in members.iter().enumerate() {
+                    let sep = if i > 0 { "," } else { "" };
+                    write!(f, "{sep}{}:{v}", Quoted(name))?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    UnexpectedEnd,
+    Unexpected(char),
+    InvalidNumber,
+    InvalidEscape,
+}
+
+/// Error found while parsing, lines and columns start from 1 and columns
+/// count characters
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            ErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ErrorKind::Unexpected(c) => write!(f, "unexpected `{c}`"),
+            ErrorKind::InvalidNumber => write!(f, "invalid number"),
+            ErrorKind::InvalidEscape => write!(f, "invalid escape sequence"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+struct Parser<'a> {
+    input: &'a str,
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn error_at(&self, at: usize, kind: ErrorKind) -> Error {
+        let before = &self.input[..at];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        Error { line, column, kind }
+    }
+
+    /// Error on the next character
+    fn unexpected(&self) -> Error {
+        let kind = match self.peek() {
+            Some(c) => ErrorKind::Unexpected(c),
+            None => ErrorKind::UnexpectedEnd,
+        };
+        self.error_at(self.at, kind)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.at..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.at += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.at += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Error> {
+        if self.peek() != Some(c) {
+            return Err(self.unexpected());
+        }
+        self.at += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Value) -> Result<Value, Error> {
+        for c in keyword.chars() {
+            self.expect(c)?;
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some('n') => self.keyword("null", Value::Null),
+            Some('t') => self.keyword("true", Value::Bool(true)),
+            Some('f') => self.keyword("false", Value::Bool(false)),
+            Some('-' | '0'..='9') => self.number(),
+            Some('"') => self.string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            _ => Err(self.unexpected()),
+        };
+        self.skip_whitespace();
+        value
+    }
+
+    fn number(&mut self) -> Result<Value, Error> {
+        let start = self.at;
+        if self.peek() == Some('-') {
+            self.at += 1;
+        }
+        while let Some('0'..='9') = self.peek() {
+            self.at += 1;
+        }
+        if let Some('.' | 'e' | 'E') = self.peek() {
+            return Err(self.error_at(start, ErrorKind::InvalidNumber));
+        }
+        let number = &self.input[start..self.at];
+        let digits = number.trim_start_matches('-');
+        let leading_zero = digits.len() > 1 && digits.starts_with('0');
+        match number.parse() {
+            Ok(n) if !leading_zero => Ok(Value::Number(n)),
+            _ => Err(self.error_at(start, ErrorKind::InvalidNumber)),
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, Error> {
+        let start = self.at;
+        let digits = self.input.get(start..start + 4);
+        // `from_str_radix` would also accept a sign
+        let digits = digits.filter(|d| d.bytes().all(|b| b.is_ascii_hexdigit()));
+        let code = digits.and_then(|d| u32::from_str_radix(d, 16).ok());
+        let code = code.ok_or_else(|| self.error_at(start, ErrorKind::InvalidEscape))?;
+        self.at += 4;
+        Ok(code)
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            let start = self.at;
+            match self.next().ok_or_else(|| self.unexpected())? {
+                '"' => return Ok(string),
+                '\\' => {
+                    let c = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let mut code = self.hex()?;
+                            // high surrogate, must be followed by a low one
+                            if (0xD800..0xDC00).contains(&code)
+                                && self.input[self.at..].starts_with("\\u")
+                            {
+                                self.at += 2;
+                                let low = self.hex()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error_at(start, ErrorKind::InvalidEscape));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            char::from_u32(code)
+                                .ok_or_else(|| self.error_at(start, ErrorKind::InvalidEscape))?
+                        }
+                        _ => return Err(self.error_at(start, ErrorKind::InvalidEscape)),
+                    };
+                    string.push(c)
+                }
+                c if c.is_control() => return Err(self.error_at(start, ErrorKind::Unexpected(c))),
+                c => string.push(c),
+            }
+        }
+    }
+
+    /// Parses the values separated by commas until `close`, calling `item`
+    /// for each one
+    fn items(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        self.at += 1;
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.at += 1;
+            return Ok(());
+        }
+        loop {
+            item(self)?;
+            match self.peek() {
+                Some(',') => self.at += 1,
+                Some(c) if c == close => {
+                    self.at += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, Error> {
+        let mut array = vec![];
+        self.items(']', |p| {
+            array.push(p.value()?);
+            Ok(())
+        })?;
+        Ok(Value::Array(array))
+    }
+
+    fn object(&mut self) -> Result<Value, Error> {
+        let mut members = vec![];
+        self.items('}', |p| {
+            p.skip_whitespace();
+            let name = p.string()?;
+            p.skip_whitespace();
+            p.expect(':')?;
+            members.push((name, p.value()?));
+            Ok(())
+        })?;
+        Ok(Value::Object(members))
+    }
+}
+
+impl FromStr for Value {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input, at: 0 };
+        let value = parser.value()?;
+        match parser.peek() {
+            None => Ok(value),
+            Some(_) => Err(parser.unexpected()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, ErrorKind, Value};
+
+    #[test]
+    fn parse() {
+        let json = r#" {"a": [1, -2, {"b": "red", "c": 4}], "d": {"e": "x\"\u00e9"}, "f": [true, false, null]} "#;
+        let value: Value = json.parse().unwrap();
+        assert_eq!(value.sum_numbers(), 3);
+        assert_eq!(value.sum_numbers_skipping(&Value::String("red".into())), -1);
+        assert_eq!(
+            value.get("d").unwrap().get("e").unwrap().as_str(),
+            Some("x\"é")
+        );
+        let compact = r#"{"a":[1,-2,{"b":"red","c":4}],"d":{"e":"x\"é"},"f":[true,false,null]}"#;
+        assert_eq!(value.to_string(), compact);
+        assert_eq!(compact.parse::<Value>(), Ok(value));
+        assert_eq!(
+            "[-0]".parse::<Value>(),
+            Ok(Value::Array(vec![Value::Number(0)]))
+        );
+        assert_eq!(
+            r#""\uD83D\uDE00""#.parse::<Value>(),
+            Ok(Value::String("😀".into()))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = |line, column, kind| Err(Error { line, column, kind });
+        assert_eq!(
+            "[1,\n 2,]".parse::<Value>(),
+            err(2, 4, ErrorKind::Unexpected(']'))
+        );
+        assert_eq!(
+            "{\"a\": tru}".parse::<Value>(),
+            err(1, 10, ErrorKind::Unexpected('}'))
+        );
+        assert_eq!(
+            "[1.5]".parse::<Value>(),
+            err(1, 2, ErrorKind::InvalidNumber)
+        );
+        assert_eq!("[01]".parse::<Value>(), err(1, 2, ErrorKind::InvalidNumber));
+        assert_eq!(
+            "[-01]".parse::<Value>(),
+            err(1, 2, ErrorKind::InvalidNumber)
+        );
+        assert_eq!(
+            r#""\uD83D\u0041""#.parse::<Value>(),
+            err(1, 2, ErrorKind::InvalidEscape)
+        );
+        assert_eq!(
+            r#""\u+041""#.parse::<Value>(),
+            err(1, 4, ErrorKind::InvalidEscape)
+        );
+        assert_eq!(
+            "\"\\q\"".parse::<Value>(),
+            err(1, 2, ErrorKind::InvalidEscape)
+        );
+        assert_eq!("[1".parse::<Value>(), err(1, 3, ErrorKind::UnexpectedEnd));
+        assert_eq!(
+            "[] x".parse::<Value>(),
+            err(1, 4, ErrorKind::Unexpected('x'))
+        );
+    }
+}
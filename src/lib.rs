@@ -1,3 +1,16 @@
+pub mod assignment;
+pub mod bits;
+pub mod brackets;
+pub mod convex;
+pub mod dot;
+pub mod flood;
+pub mod geometry;
+pub mod graph;
+pub mod json;
+pub mod matrix;
+pub mod turtle;
+pub mod vm;
+
 pub mod const_utils {
     pub const fn split_left_at<T>(mut slice: &[T], at: usize) -> &[T] {
         while slice.len() != at {
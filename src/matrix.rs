@@ -0,0 +1,201 @@
+//! Integer matrices, mainly to compute linear recurrences with fast
+//! exponentiation.
+
+use std::ops::{Index, IndexMut, Mul};
+
+/// Matrix of non-negative integers, whose arithmetic is done modulo
+/// `modulus` when it's set
+///
+/// Without a modulus the operations panic on overflow.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    cells: Vec<u64>,
+    modulus: Option<u64>,
+}
+
+impl Matrix {
+    pub fn zero(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            cells: vec![0; rows * cols],
+            modulus: None,
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut matrix = Self::zero(n, n);
+        (0..n).for_each(|i| matrix[(i, i)] = 1);
+        matrix
+    }
+
+    /// Builds the matrix from its rows, which must all have the same length
+    pub fn from_rows(rows: &[&[u64]]) -> Self {
+        let cols = rows.first().map_or(0, |r| r.len());
+        assert!(rows.iter().all(|r| r.len() == cols), "ragged rows");
+        Self {
+            rows: rows.len(),
+            cols,
+            cells: rows.concat(),
+            modulus: None,
+        }
+    }
+
+    /// Transition matrix of a system with `states` states, where each
+    /// individual in a state turns into one individual in each of the states
+    /// returned by `rule`
+    ///
+    /// Multiplying it by a histogram of the states gives the histogram after
+    /// one step.
+    pub fn transition(states: usize, rule: impl Fn(usize) -> Vec<usize>) -> Self {
+        let mut matrix = Self::zero(states, states);
+        for from in 0..states {
+            for to in rule(from) {
+                matrix[(to, from)] += 1;
+            }
+        }
+        matrix
+    }
+
+    /// Reduces all the values modulo `modulus`, which is then used for all
+    /// the operations
+    pub fn with_modulus(mut self, modulus: u64) -> Self {
+        assert!(modulus > 0, "modulus must be positive");
+        self.cells.iter_mut().for_each(|c| *c %= modulus);
+        self.modulus = Some(modulus);
+        self
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn modulus(&self) -> Option<u64> {
+        self.modulus
+    }
+
+    /// Sum of the products of the pairs
+    fn dot(&self, pairs: impl Iterator<Item = (u64, u64)>) -> u64 {
+        let mut products = pairs.map(|(a, b)| a as u128 * b as u128);
+        match self.modulus {
+            // reduce at each step as the sum of the products may not fit in
+            // a u128 with a large modulus
+            Some(m) => products.fold(0, |acc, p| (acc + p % m as u128) % m as u128) as u64,
+            None => products
+                .try_fold(0u64, |acc, p| acc.checked_add(u64::try_from(p).ok()?))
+                .expect("matrix overflow"),
+        }
+    }
+
+    /// Product of the matrix with a column vector
+    pub fn apply(&self, vector: &[u64]) -> Vec<u64> {
+        assert_eq!(self.cols, vector.len(), "size mismatch");
+        (0..self.rows)
+            .map(|r| {
+                let row = &self.cells[r * self.cols..][..self.cols];
+                self.dot(row.iter().copied().zip(vector.iter().copied()))
+            })
+            .collect()
+    }
+
+    /// Raises the square matrix to the power of `exp` by repeated squaring
+    pub fn pow(&self, mut exp: u64) -> Self {
+        assert_eq!(self.rows, self.cols, "not a square matrix");
+        let mut result = Self::identity(self.rows);
+        result.modulus = self.modulus;
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// Histogram of the states after `steps` steps of the transition matrix,
+    /// starting from `histogram`
+    pub fn evolve(&self, histogram: &[u64], steps: u64) -> Vec<u64> {
+        self.pow(steps).apply(histogram)
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = u64;
+
+    fn index(&self, (r, c): (usize, usize)) -> &u64 {
+        assert!(r < self.rows && c < self.cols, "index out of bounds");
+        &self.cells[r * self.cols + c]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (r, c): (usize, usize)) -> &mut u64 {
+        assert!(r < self.rows && c < self.cols, "index out of bounds");
+        &mut self.cells[r * self.cols + c]
+    }
+}
+
+/// The result keeps the modulus of the operands, which must be the same
+impl Mul for &Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: &Matrix) -> Matrix {
+        assert_eq!(self.cols, rhs.rows, "size mismatch");
+        assert_eq!(self.modulus, rhs.modulus, "modulus mismatch");
+        let mut result = Matrix::zero(self.rows, rhs.cols);
+        result.modulus = self.modulus;
+        for r in 0..self.rows {
+            for c in 0..rhs.cols {
+                result[(r, c)] = self.dot((0..self.cols).map(|i| (self[(r, i)], rhs[(i, c)])));
+            }
+        }
+        result
+    }
+}
+
+/// Counts how many of the values are equal to each state from `0` to
+/// `states - 1`
+pub fn histogram(values: impl IntoIterator<Item = usize>, states: usize) -> Vec<u64> {
+    let mut histogram = vec![0; states];
+    values.into_iter().for_each(|v| histogram[v] += 1);
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{histogram, Matrix};
+
+    #[test]
+    fn fibonacci() {
+        let fib = Matrix::from_rows(&[&[1, 1], &[1, 0]]);
+        assert_eq!(fib.pow(40)[(0, 1)], 102334155);
+        assert_eq!(fib.pow(0), Matrix::identity(2));
+        // the last digit of the fibonacci numbers repeats every 60 of them
+        let fib = fib.with_modulus(10);
+        assert_eq!(fib.pow(1_000_000_000_000_000_000)[(0, 1)], 5);
+    }
+
+    #[test]
+    fn transition() {
+        // each state moves to the next one, the last one splits
+        let matrix = Matrix::transition(3, |s| match s {
+            2 => vec![0, 2],
+            s => vec![s + 1],
+        });
+        let start = histogram([0, 0, 1], 3);
+        assert_eq!(start, [2, 1, 0]);
+        assert_eq!(matrix.apply(&start), [0, 2, 1]);
+        assert_eq!(matrix.evolve(&start, 3), [3, 1, 3]);
+        assert_eq!(&matrix.pow(2) * &matrix, matrix.pow(3));
+    }
+}
@@ -0,0 +1,343 @@
+//! Movement commands, made of a keyword and an amount, and turtles that move
+//! around the lattice following them.
+
+use crate::geometry::Point;
+use std::{fmt, num::ParseIntError};
+
+/// What a command does for each unit of its amount
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Moves by the given step, what moves depends on the turtle
+    Move(Point),
+    /// Turns a quarter of a turn counterclockwise, clockwise if negative,
+    /// for every 90 degrees of the amount
+    Turn(i64),
+    /// Goes ahead in the direction the turtle is heading
+    Forward,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Command {
+    pub action: Action,
+    /// Number of times to repeat the action, in degrees for the turns
+    pub amount: u64,
+}
+
+impl Command {
+    /// Number of times the action is repeated
+    pub fn times(&self) -> u64 {
+        match self.action {
+            Action::Turn(_) => self.amount / 90,
+            _ => self.amount,
+        }
+    }
+
+    /// The action repeated once for each unit of the amount
+    pub fn actions(&self) -> impl Iterator<Item = Action> {
+        std::iter::repeat_n(self.action, self.times() as usize)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The line doesn't start with any keyword, or with a vector if those are
+    /// accepted
+    UnknownKeyword,
+    BadAmount(ParseIntError),
+    /// Turn of the given degrees
+    TurnNotMultipleOf90(u64),
+}
+
+/// Error found while parsing, lines start from 1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ErrorKind::UnknownKeyword => write!(f, "unknown command"),
+            ErrorKind::BadAmount(err) => write!(f, "invalid amount, {err}"),
+            ErrorKind::TurnNotMultipleOf90(degrees) => {
+                write!(f, "turn of {degrees} degrees, not a multiple of 90")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Table of the keywords of a command language
+///
+/// A command is a keyword followed by its amount, optionally separated by
+/// spaces. When keywords start the same way the longest one is used.
+#[derive(Clone, Debug)]
+pub struct Language {
+    keywords: Vec<(String, Action)>,
+    vectors: bool,
+}
+
+impl Language {
+    pub fn new(keywords: &[(&str, Action)]) -> Self {
+        let keywords = keywords.iter().map(|&(k, a)| (k.to_string(), a));
+        Self {
+            keywords: keywords.collect(),
+            vectors: false,
+        }
+    }
+
+    /// Also accepts moves given as a step vector, like `2,-1 3`
+    pub fn with_vectors(self) -> Self {
+        Self {
+            vectors: true,
+            ..self
+        }
+    }
+
+    fn action(&self, line: &str) -> Option<(Action, usize)> {
+        let keyword = self
+            .keywords
+            .iter()
+            .filter(|(k, _)| line.starts_with(k.as_str()))
+            .max_by_key(|(k, _)| k.len());
+        if let Some((k, action)) = keyword {
+            return Some((*action, k.len()));
+        }
+        let (vector, _) = line.split_once(' ').filter(|_| self.vectors)?;
+        let (x, y) = vector.split_once(',')?;
+        let step = Point::new(x.parse().ok()?, y.parse().ok()?);
+        Some((Action::Move(step), vector.len()))
+    }
+
+    fn parse_kind(&self, text: &str) -> Result<Command, ErrorKind> {
+        let (action, len) = self.action(text).ok_or(ErrorKind::UnknownKeyword)?;
+        let amount = text[len..].trim_start().parse();
+        let amount = amount.map_err(ErrorKind::BadAmount)?;
+        if matches!(action, Action::Turn(_)) && amount % 90 != 0 {
+            return Err(ErrorKind::TurnNotMultipleOf90(amount));
+        }
+        Ok(Command { action, amount })
+    }
+
+    /// Parses a line, turns must be of a multiple of 90 degrees
+    pub fn parse(&self, text: &str) -> Result<Command, Error> {
+        self.parse_kind(text)
+            .map_err(|kind| Error { line: 1, kind })
+    }
+
+    /// Parses a command on each line
+    pub fn parse_all(&self, input: &str) -> Result<Vec<Command>, Error> {
+        let commands = input
+            .lines()
+            .zip(1..)
+            .map(|(text, line)| self.parse_kind(text).map_err(|kind| Error { line, kind }));
+        commands.collect()
+    }
+}
+
+fn add(a: Point, b: Point, times: u64) -> Point {
+    let times = times as i64;
+    Point::new(a.x + b.x * times, a.y + b.y * times)
+}
+
+/// Rotates the vector by quarter turns, counterclockwise if positive
+fn rotate(v: Point, quarters: i64) -> Point {
+    match quarters.rem_euclid(4) {
+        0 => v,
+        1 => Point::new(-v.y, v.x),
+        2 => Point::new(-v.x, -v.y),
+        _ => Point::new(v.y, -v.x),
+    }
+}
+
+/// State that moves following the actions
+pub trait Turtle {
+    fn position(&self) -> Point;
+
+    fn apply(&mut self, action: Action);
+
+    /// Applies the action `times` times in a row
+    fn apply_n(&mut self, action: Action, times: u64) {
+        (0..times).for_each(|_| self.apply(action));
+    }
+}
+
+/// Turtle that moves itself and goes forward in the direction it's heading
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Heading {
+    pub position: Point,
+    pub heading: Point,
+}
+
+impl Heading {
+    pub fn new(position: Point, heading: Point) -> Self {
+        Self { position, heading }
+    }
+}
+
+impl Turtle for Heading {
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn apply(&mut self, action: Action) {
+        self.apply_n(action, 1)
+    }
+
+    fn apply_n(&mut self, action: Action, times: u64) {
+        match action {
+            Action::Move(step) => self.position = add(self.position, step, times),
+            Action::Turn(quarters) => {
+                self.heading = rotate(self.heading, quarters * (times % 4) as i64)
+            }
+            Action::Forward => self.position = add(self.position, self.heading, times),
+        }
+    }
+}
+
+/// Turtle that moves a waypoint, relative to its position, and goes forward
+/// to the waypoint
+///
+/// A waypoint one step ahead works as an aim: the moves across the heading
+/// change how much going forward drifts sideways.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Waypoint {
+    pub position: Point,
+    pub waypoint: Point,
+}
+
+impl Waypoint {
+    pub fn new(position: Point, waypoint: Point) -> Self {
+        Self { position, waypoint }
+    }
+}
+
+impl Turtle for Waypoint {
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn apply(&mut self, action: Action) {
+        self.apply_n(action, 1)
+    }
+
+    fn apply_n(&mut self, action: Action, times: u64) {
+        match action {
+            Action::Move(step) => self.waypoint = add(self.waypoint, step, times),
+            Action::Turn(quarters) => {
+                self.waypoint = rotate(self.waypoint, quarters * (times % 4) as i64)
+            }
+            Action::Forward => self.position = add(self.position, self.waypoint, times),
+        }
+    }
+}
+
+/// Applies the commands to the turtle
+pub fn run<'a>(turtle: &mut impl Turtle, commands: impl IntoIterator<Item = &'a Command>) {
+    for command in commands {
+        turtle.apply_n(command.action, command.times());
+    }
+}
+
+/// Applies the commands to the turtle one unit at a time, returning every
+/// position it goes through, starting with the initial one
+pub fn trajectory<'a>(
+    turtle: &mut impl Turtle,
+    commands: impl IntoIterator<Item = &'a Command>,
+) -> Vec<Point> {
+    let mut positions = vec![turtle.position()];
+    for command in commands {
+        for action in command.actions() {
+            turtle.apply(action);
+            if positions.last() != Some(&turtle.position()) {
+                positions.push(turtle.position());
+            }
+        }
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, trajectory, Action, Command, Error, ErrorKind, Heading, Language, Waypoint};
+    use crate::geometry::Point;
+
+    fn language() -> Language {
+        Language::new(&[
+            ("N", Action::Move(Point::new(0, 1))),
+            ("E", Action::Move(Point::new(1, 0))),
+            ("NE", Action::Move(Point::new(1, 1))),
+            ("L", Action::Turn(1)),
+            ("R", Action::Turn(-1)),
+            ("F", Action::Forward),
+        ])
+    }
+
+    #[test]
+    fn parse() {
+        let language = language();
+        let command = |action, amount| Ok(Command { action, amount });
+        assert_eq!(language.parse("F10"), command(Action::Forward, 10));
+        assert_eq!(
+            language.parse("NE  3"),
+            command(Action::Move(Point::new(1, 1)), 3)
+        );
+        assert_eq!(language.parse("R270"), command(Action::Turn(-1), 270));
+        let err = |line, kind| Err(Error { line, kind });
+        assert_eq!(
+            language.parse("R45"),
+            err(1, ErrorKind::TurnNotMultipleOf90(45))
+        );
+        assert_eq!(language.parse("1,2 3"), err(1, ErrorKind::UnknownKeyword));
+        let error = language.parse_all("F1\nX1").unwrap_err();
+        assert_eq!(
+            error,
+            Error {
+                line: 2,
+                kind: ErrorKind::UnknownKeyword
+            }
+        );
+        assert_eq!(error.to_string(), "line 2: unknown command");
+        let error = language.parse_all("N1\nL90\nF-1").unwrap_err();
+        assert_eq!(error.line, 3);
+        assert!(matches!(error.kind, ErrorKind::BadAmount(_)));
+        let language = language.with_vectors();
+        assert_eq!(
+            language.parse("1,-2 3"),
+            command(Action::Move(Point::new(1, -2)), 3)
+        );
+    }
+
+    #[test]
+    fn turtles() {
+        let commands = language().parse_all("F2\nL90\nNE1\nF1\nR180").unwrap();
+        let mut turtle = Heading::new(Point::new(0, 0), Point::new(1, 0));
+        let points: Vec<_> = trajectory(&mut turtle, &commands)
+            .into_iter()
+            .map(|p| (p.x, p.y))
+            .collect();
+        assert_eq!(points, [(0, 0), (1, 0), (2, 0), (3, 1), (3, 2)]);
+        assert_eq!(turtle.heading, Point::new(0, -1));
+
+        let mut turtle = Waypoint::new(Point::new(0, 0), Point::new(2, 1));
+        run(&mut turtle, &commands);
+        assert_eq!(turtle.position, Point::new(4, 5));
+        assert_eq!(turtle.waypoint, Point::new(0, -3));
+
+        // whole commands at once end up where single units do
+        let mut unit = Heading::new(Point::new(0, 0), Point::new(1, 0));
+        let mut whole = unit;
+        trajectory(&mut unit, &commands);
+        run(&mut whole, &commands);
+        assert_eq!(unit, whole);
+
+        let far = language().parse_all("F1000000000000\nL270\nF3").unwrap();
+        let mut turtle = Waypoint::new(Point::new(0, 0), Point::new(2, 1));
+        run(&mut turtle, &far);
+        let end = Point::new(2_000_000_000_003, 999_999_999_994);
+        assert_eq!(turtle.position, end);
+    }
+}
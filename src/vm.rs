@@ -0,0 +1,429 @@
+//! A small virtual machine for assembly-like puzzles made of a list of
+//! instructions, an instruction pointer and a single accumulator.
+
+use crate::dot::Dot;
+
+/// An instruction that can be executed by the [`Vm`]
+pub trait Instruction {
+    /// Executes the instruction, updating the accumulator, and returns the
+    /// offset to add to the instruction pointer (`1` to go to the next one)
+    fn execute(&self, acc: &mut i64) -> isize;
+}
+
+/// A single executed instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// Index of the executed instruction
+    pub ip: usize,
+    /// Value of the accumulator before the execution
+    pub acc: i64,
+}
+
+/// An instruction loop found while running the program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    /// Index of the first step of the loop in the trace
+    pub start: usize,
+    /// Indices of the instructions of the loop, in execution order
+    pub ips: Vec<usize>,
+}
+
+/// Condition on the accumulator that stops the execution
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Watch {
+    /// The accumulator changes value
+    Change,
+    /// The accumulator becomes equal to the given value
+    Value(i64),
+}
+
+impl Watch {
+    fn triggered(self, old: i64, new: i64) -> bool {
+        match self {
+            Watch::Change => old != new,
+            Watch::Value(v) => old != new && new == v,
+        }
+    }
+}
+
+/// Reason why the execution stopped
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Halt {
+    /// The instruction pointer went right after the last instruction
+    Terminated,
+    /// The instruction pointer went outside of the program
+    OutOfBounds(isize),
+    /// The next instruction was already executed
+    Loop(Cycle),
+    /// The next instruction has a breakpoint on it
+    Breakpoint(usize),
+    /// The accumulator met the condition of a watchpoint
+    Watchpoint { watch: Watch, old: i64, new: i64 },
+}
+
+/// Virtual machine that runs a program made of instructions of type `I`
+///
+/// Every executed instruction is recorded in the trace, which is also used to
+/// detect loops: as the control flow only depends on the instruction pointer,
+/// executing the same instruction twice means the program never terminates.
+#[derive(Clone, Debug)]
+pub struct Vm<I> {
+    program: Vec<I>,
+    ip: isize,
+    acc: i64,
+    trace: Vec<Step>,
+    /// Position in the trace of the execution of each instruction
+    executed: Vec<Option<usize>>,
+    breakpoints: Vec<bool>,
+    /// Stopped at the breakpoint of the next instruction, which is skipped
+    /// when the execution is resumed
+    paused: bool,
+    watchpoints: Vec<Watch>,
+}
+
+impl<I: Instruction> Vm<I> {
+    pub fn new(program: Vec<I>) -> Self {
+        let len = program.len();
+        Self {
+            program,
+            ip: 0,
+            acc: 0,
+            trace: vec![],
+            executed: vec![None; len],
+            breakpoints: vec![false; len],
+            paused: false,
+            watchpoints: vec![],
+        }
+    }
+
+    pub fn program(&self) -> &[I] {
+        &self.program
+    }
+
+    /// Mutable access to the instructions, the execution should be [`reset`]
+    /// after changing them
+    ///
+    /// [`reset`]: Self::reset
+    pub fn program_mut(&mut self) -> &mut [I] {
+        &mut self.program
+    }
+
+    pub fn ip(&self) -> isize {
+        self.ip
+    }
+
+    pub fn acc(&self) -> i64 {
+        self.acc
+    }
+
+    pub fn trace(&self) -> &[Step] {
+        &self.trace
+    }
+
+    /// Restarts the execution from the first instruction, keeping the
+    /// breakpoints and the watchpoints
+    pub fn reset(&mut self) {
+        self.ip = 0;
+        self.acc = 0;
+        self.trace.clear();
+        self.executed.iter_mut().for_each(|e| *e = None);
+        self.paused = false;
+    }
+
+    pub fn set_breakpoint(&mut self, ip: usize) {
+        self.breakpoints[ip] = true;
+    }
+
+    pub fn clear_breakpoint(&mut self, ip: usize) {
+        self.breakpoints[ip] = false;
+    }
+
+    pub fn watch(&mut self, watch: Watch) {
+        self.watchpoints.push(watch);
+    }
+
+    pub fn unwatch(&mut self, watch: Watch) {
+        self.watchpoints.retain(|&w| w != watch);
+    }
+
+    /// Index of the next instruction if it's inside of the program
+    fn next_ip(&self) -> Result<usize, Halt> {
+        match usize::try_from(self.ip) {
+            Ok(ip) if ip < self.program.len() => Ok(ip),
+            Ok(ip) if ip == self.program.len() => Err(Halt::Terminated),
+            _ => Err(Halt::OutOfBounds(self.ip)),
+        }
+    }
+
+    /// Executes the next instruction, ignoring breakpoints
+    ///
+    /// Returns the reason why the execution can't continue, if the next
+    /// instruction can't be executed, or the watchpoint that was triggered
+    /// by this instruction.
+    pub fn step(&mut self) -> Option<Halt> {
+        let ip = match self.next_ip() {
+            Ok(ip) => ip,
+            Err(halt) => return Some(halt),
+        };
+        if let Some(start) = self.executed[ip] {
+            let ips = self.trace[start..].iter().map(|s| s.ip).collect();
+            return Some(Halt::Loop(Cycle { start, ips }));
+        }
+        let old = self.acc;
+        self.paused = false;
+        self.executed[ip] = Some(self.trace.len());
+        self.trace.push(Step { ip, acc: old });
+        self.ip += self.program[ip].execute(&mut self.acc);
+
+        let new = self.acc;
+        let watch = self.watchpoints.iter().find(|w| w.triggered(old, new));
+        watch.map(|&watch| Halt::Watchpoint { watch, old, new })
+    }
+
+    /// Runs the program until it halts
+    ///
+    /// A breakpoint stops the execution before its instruction, which is
+    /// executed when running again, so that the execution can be resumed
+    /// after stopping.
+    pub fn run(&mut self) -> Halt {
+        loop {
+            if let Ok(ip) = self.next_ip() {
+                if self.breakpoints[ip] && !self.paused {
+                    self.paused = true;
+                    return Halt::Breakpoint(ip);
+                }
+            }
+            if let Some(halt) = self.step() {
+                return halt;
+            }
+        }
+    }
+}
+
+/// Control flow graph of a program
+///
+/// Like the [`Vm`], it assumes that the control flow doesn't depend on the
+/// value of the accumulator, so each instruction has exactly one successor.
+#[derive(Clone, Debug)]
+pub struct Cfg {
+    /// Index of the instruction executed after each one, which may be
+    /// outside of the program
+    next: Vec<isize>,
+}
+
+impl Cfg {
+    pub fn new<I: Instruction>(program: &[I]) -> Self {
+        let next = program
+            .iter()
+            .enumerate()
+            .map(|(ip, i)| ip as isize + i.execute(&mut 0))
+            .collect();
+        Self { next }
+    }
+
+    pub fn len(&self) -> usize {
+        self.next.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next.is_empty()
+    }
+
+    /// Index of the instruction executed after `ip`, equal to the length of
+    /// the program if it terminates or `None` if it jumps outside of it
+    pub fn successor(&self, ip: usize) -> Option<usize> {
+        usize::try_from(self.next[ip])
+            .ok()
+            .filter(|&next| next <= self.len())
+    }
+
+    /// Marks the instructions from which the program terminates
+    pub fn terminating(&self) -> Vec<bool> {
+        let len = self.len();
+        let mut prev = vec![vec![]; len + 1];
+        for ip in 0..len {
+            if let Some(next) = self.successor(ip) {
+                prev[next].push(ip);
+            }
+        }
+        let mut terminating = vec![false; len + 1];
+        let mut stack = vec![len];
+        while let Some(ip) = stack.pop() {
+            if !std::mem::replace(&mut terminating[ip], true) {
+                stack.extend(&prev[ip]);
+            }
+        }
+        terminating.pop();
+        terminating
+    }
+
+    /// Instructions executed starting from the first one and the reason why
+    /// the execution stops, which is never [`Halt::Breakpoint`] or
+    /// [`Halt::Watchpoint`]
+    pub fn path(&self) -> (Vec<usize>, Halt) {
+        let mut executed = vec![None; self.len()];
+        let mut path = vec![];
+        let mut ip = 0;
+        loop {
+            if ip == self.len() {
+                return (path, Halt::Terminated);
+            }
+            if let Some(start) = executed[ip] {
+                let ips = path[start..].to_vec();
+                return (path, Halt::Loop(Cycle { start, ips }));
+            }
+            executed[ip] = Some(path.len());
+            path.push(ip);
+            match self.successor(ip) {
+                Some(next) => ip = next,
+                None => return (path, Halt::OutOfBounds(self.next[ip])),
+            }
+        }
+    }
+
+    /// Finds the instruction that makes the program terminate when its jump
+    /// offset is replaced by the one returned by `alt`
+    ///
+    /// Returns `None` if the program already terminates or if no such
+    /// instruction exists.
+    pub fn repair(&self, alt: impl Fn(usize) -> Option<isize>) -> Option<usize> {
+        let (path, Halt::Loop(_) | Halt::OutOfBounds(_)) = self.path() else {
+            return None;
+        };
+        let terminating = self.terminating();
+        path.into_iter().find(|&ip| {
+            let next = alt(ip).and_then(|offset| ip.checked_add_signed(offset));
+            next.is_some_and(|n| n == self.len() || terminating.get(n) == Some(&true))
+        })
+    }
+
+    /// Graphviz DOT representation of the graph, the instructions are
+    /// labeled with `label` and the terminating ones are highlighted
+    pub fn dot(&self, label: impl Fn(usize) -> String) -> Dot {
+        let mut dot = Dot::new();
+        for ip in 0..self.len() {
+            dot.node(format!("{ip}: {}", label(ip)));
+        }
+        let end = dot.node("end");
+        let mut out = None;
+        for ip in 0..self.len() {
+            match self.successor(ip) {
+                Some(next) => dot.edge(ip, next),
+                None => {
+                    let out = *out.get_or_insert_with(|| dot.node("out"));
+                    dot.edge(ip, out)
+                }
+            }
+        }
+        let terminating = self.terminating().into_iter().enumerate();
+        let terminating = terminating.filter_map(|(ip, t)| t.then_some(ip));
+        dot.highlight(terminating.chain([end]), "palegreen");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    enum Cmd {
+        Nop,
+        Jmp(isize),
+        Acc(i64),
+    }
+
+    impl Instruction for Cmd {
+        fn execute(&self, acc: &mut i64) -> isize {
+            match *self {
+                Cmd::Nop => 1,
+                Cmd::Jmp(n) => n,
+                Cmd::Acc(n) => {
+                    *acc += n;
+                    1
+                }
+            }
+        }
+    }
+
+    fn looping() -> Vm<Cmd> {
+        use Cmd::*;
+        Vm::new(vec![Acc(1), Nop, Acc(2), Jmp(-2), Acc(5)])
+    }
+
+    #[test]
+    fn loops() {
+        let mut vm = looping();
+        let cycle = Cycle {
+            start: 1,
+            ips: vec![1, 2, 3],
+        };
+        assert_eq!(vm.run(), Halt::Loop(cycle));
+        assert_eq!(vm.acc(), 3);
+        assert_eq!(vm.trace().len(), 4);
+    }
+
+    #[test]
+    fn terminates() {
+        let mut vm = looping();
+        vm.program_mut()[3] = Cmd::Nop;
+        assert_eq!(vm.run(), Halt::Terminated);
+        assert_eq!(vm.acc(), 8);
+    }
+
+    #[test]
+    fn breakpoints_and_watchpoints() {
+        let mut vm = looping();
+        vm.set_breakpoint(2);
+        vm.watch(Watch::Value(3));
+        assert_eq!(vm.run(), Halt::Breakpoint(2));
+        assert_eq!(vm.acc(), 1);
+        let watch = Watch::Value(3);
+        assert_eq!(
+            vm.run(),
+            Halt::Watchpoint {
+                watch,
+                old: 1,
+                new: 3
+            }
+        );
+        assert_eq!(vm.ip(), 3);
+        assert_eq!(vm.step(), None);
+        assert!(matches!(vm.run(), Halt::Loop(_)));
+    }
+
+    #[test]
+    fn breakpoint_on_first_instruction() {
+        let mut vm = looping();
+        vm.set_breakpoint(0);
+        assert_eq!(vm.run(), Halt::Breakpoint(0));
+        assert_eq!(vm.trace().len(), 0);
+        assert!(matches!(vm.run(), Halt::Loop(_)));
+        vm.reset();
+        assert_eq!(vm.run(), Halt::Breakpoint(0));
+
+        // stepping past a breakpoint doesn't skip the next one
+        vm.reset();
+        vm.set_breakpoint(1);
+        assert_eq!(vm.run(), Halt::Breakpoint(0));
+        assert_eq!(vm.step(), None);
+        assert_eq!(vm.run(), Halt::Breakpoint(1));
+        assert_eq!(vm.acc(), 1);
+    }
+
+    #[test]
+    fn cfg() {
+        let mut vm = looping();
+        let cfg = Cfg::new(vm.program());
+        assert_eq!(cfg.terminating(), [false, false, false, false, true]);
+        let cycle = Cycle {
+            start: 1,
+            ips: vec![1, 2, 3],
+        };
+        assert_eq!(cfg.path(), (vec![0, 1, 2, 3], Halt::Loop(cycle)));
+
+        let alt = |ip| matches!(vm.program()[ip], Cmd::Nop | Cmd::Jmp(_)).then_some(1);
+        assert_eq!(cfg.repair(alt), Some(3));
+        vm.program_mut()[3] = Cmd::Nop;
+        assert_eq!(Cfg::new(vm.program()).repair(|_| Some(1)), None);
+    }
+}